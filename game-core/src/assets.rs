@@ -0,0 +1,132 @@
+use amethyst::{
+    assets::ProgressCounter,
+    prelude::*,
+    renderer::SpriteSheetHandle,
+    utils::application_root_dir,
+};
+use component::{Animation, AnimationSet};
+use crate::error::{Error, Result};
+use crate::load;
+use crate::map;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Sprite sheets the code refers to. Enemy sheets are named by the archetypes
+/// in `enemies.ron`, projectile sheets by the weapons in `weapons.ron`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum SheetId {
+    Player,
+    Enemy(String),
+    Projectile(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum AnimationId {
+    Player,
+    Ally,
+    Enemy(String),
+    Projectile(String),
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SheetDef {
+    pub texture: String,
+    pub sheet: String,
+}
+
+/// Contents of `assets.ron`. Paths are relative to the resources directory.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Manifest {
+    pub sprite_sheets: HashMap<SheetId, SheetDef>,
+    /// Tiled tilesets by the name given in the tsx.
+    pub tilesets: HashMap<String, String>,
+    /// Clip files, see `component::animation::AnimationSet`.
+    pub animations: HashMap<AnimationId, String>,
+}
+
+impl Manifest {
+    pub fn read() -> Result<Self> {
+        read_resource("assets.ron")
+    }
+}
+
+fn read_resource<T: Config>(file: &str) -> Result<T> {
+    let path = format!("{}/resources/{}", application_root_dir(), file);
+    match T::load_no_fallback(&path) {
+        Ok(value) => Ok(value),
+        Err(cause) => Err(Error::Config { path, cause }),
+    }
+}
+
+/// Everything listed in the manifest, loaded and looked up by id.
+#[derive(Default)]
+pub struct Assets {
+    sheets: HashMap<SheetId, SpriteSheetHandle>,
+    tilesets: HashMap<String, SpriteSheetHandle>,
+    animations: HashMap<AnimationId, Arc<AnimationSet>>,
+}
+
+impl Assets {
+    /// Starts loading every asset in `manifest`. The handles are usable right
+    /// away, `progress` tells when the data behind them has arrived.
+    pub fn load(
+        world: &mut World,
+        manifest: Manifest,
+        progress: &mut ProgressCounter,
+    ) -> Result<Self> {
+        let mut assets = Assets::default();
+
+        for (id, def) in manifest.sprite_sheets {
+            let handle = load::sprite_sheet(world, &def.texture, &def.sheet, progress);
+            assets.sheets.insert(id, handle);
+        }
+
+        for (name, tsx) in manifest.tilesets {
+            let path = format!("{}/resources/{}", application_root_dir(), tsx);
+            let tileset =
+                tilesheet::Tileset::open(&path).map_err(|cause| Error::Tileset { path, cause })?;
+
+            let handle = map::tileset_sprite_sheet(world, &tileset, progress);
+            assets.tilesets.insert(name, handle);
+        }
+
+        for (id, file) in manifest.animations {
+            let set: AnimationSet = read_resource(&file)?;
+            assets.animations.insert(id, Arc::new(set));
+        }
+
+        Ok(assets)
+    }
+
+    pub fn sheet(&self, id: &SheetId) -> Result<SpriteSheetHandle> {
+        self.sheets
+            .get(id)
+            .cloned()
+            .ok_or_else(|| Error::UnknownSheet(id.clone()))
+    }
+
+    pub fn tileset(&self, name: &str) -> Result<SpriteSheetHandle> {
+        self.tilesets
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownTileset(name.into()))
+    }
+
+    /// Sheet and animation for spawning a sprite in one lookup.
+    pub fn sprite(
+        &self,
+        sheet: &SheetId,
+        animation: &AnimationId,
+    ) -> Result<(SpriteSheetHandle, Animation)> {
+        Ok((self.sheet(sheet)?, self.animation(animation)?))
+    }
+
+    /// A fresh `Animation` component for `id`.
+    pub fn animation(&self, id: &AnimationId) -> Result<Animation> {
+        self.animations
+            .get(id)
+            .map(|set| Animation::new(set.clone()))
+            .ok_or_else(|| Error::UnknownAnimation(id.clone()))
+    }
+}
@@ -0,0 +1,57 @@
+use amethyst::{
+    core::cgmath::Vector2,
+    ecs::{Component, DenseVecStorage},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiState {
+    Idle,
+    Wander,
+    Chase,
+    Attack,
+    Flee,
+}
+
+pub struct Ai {
+    pub state: AiState,
+    pub timer: f32,            // seconds left in Idle or Wander
+    pub heading: Vector2<f32>, // unit vector used while wandering
+}
+
+impl Default for Ai {
+    fn default() -> Self {
+        Self::idle(1.0)
+    }
+}
+
+impl Component for Ai {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Ai {
+    pub fn idle(seconds: f32) -> Self {
+        Self {
+            state: AiState::Idle,
+            timer: seconds,
+            heading: Vector2 { x: 0.0, y: 0.0 },
+        }
+    }
+
+    pub fn set_idle(&mut self, seconds: f32) {
+        self.state = AiState::Idle;
+        self.timer = seconds;
+    }
+
+    pub fn set_wander(&mut self, heading: Vector2<f32>, seconds: f32) {
+        self.state = AiState::Wander;
+        self.timer = seconds;
+        self.heading = heading;
+    }
+
+    /// Chase, Attack and Flee only last as long as the player is in sight, so
+    /// they don't use the timer.
+    pub fn set_engaged(&mut self, state: AiState) {
+        self.state = state;
+        self.timer = 0.0;
+    }
+}
@@ -1,15 +1,55 @@
-use amethyst::ecs::{Component, DenseVecStorage};
+use amethyst::ecs::{Component, NullStorage};
+use amethyst::{
+    core::cgmath::Vector2,
+    core::Transform,
+    ecs::Entity,
+    prelude::*,
+    renderer::{SpriteRender, SpriteSheetHandle, Transparent},
+};
+use crate::component::{Animation, Collider, Health};
 
-pub struct Ally {
-    pub hp: u32,
-}
+pub struct Ally;
 
 impl Default for Ally {
     fn default() -> Self {
-        Self { hp: 10 }
+        Ally
     }
 }
 
 impl Component for Ally {
-    type Storage = DenseVecStorage<Self>;
+    type Storage = NullStorage<Self>;
+}
+
+impl Ally {
+    /// Creates an ally pickup waiting to be recruited by the player.
+    pub fn new(
+        world: &mut World,
+        sprite_sheet: &SpriteSheetHandle,
+        animation: Animation,
+        position: Vector2<f32>,
+    ) -> Entity {
+        let mut transform = Transform::default();
+        transform.scale.x = 0.5;
+        transform.scale.y = 0.5;
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+
+        let sprite = SpriteRender {
+            sprite_sheet: sprite_sheet.clone(),
+            sprite_number: 1,
+            flip_horizontal: false,
+            flip_vertical: false,
+        };
+
+        world
+            .create_entity()
+            .with(transform)
+            .with(Ally)
+            .with(Health::new(10))
+            .with(sprite)
+            .with(Transparent)
+            .with(animation)
+            .with(Collider::from_sprite(32.0, 32.0, 0.5))
+            .build()
+    }
 }
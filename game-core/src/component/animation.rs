@@ -2,23 +2,118 @@ use amethyst::{
     ecs::{Component, DenseVecStorage},
     renderer::SpriteRender,
 };
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
 
-pub struct Animation {
-    pub total_frames: usize,
-    pub max_count_till_next_frame: f32, // These are in seconds
-    pub frame_life_time_count: f32,     // These are in seconds
-    pub current_frame: usize,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum ClipName {
+    Idle,
+    WalkUp,
+    WalkDown,
+    WalkLeft,
+    WalkRight,
+    Attack,
+    Hurt,
+    Die,
+}
+
+impl ClipName {
+    /// One-shot actions that movement shouldn't cut short.
+    pub fn is_action(self) -> bool {
+        match self {
+            ClipName::Attack | ClipName::Hurt | ClipName::Die => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ClipMode {
+    Loop,
+    /// Plays once and holds the last frame.
+    Once,
+    /// Plays to the end, then back to the start, and so on.
+    PingPong,
 }
 
-impl Default for Animation {
+impl Default for ClipMode {
     fn default() -> Self {
-        Self {
-            total_frames: 0,
-            max_count_till_next_frame: 0.0,
-            frame_life_time_count: 0.0,
-            current_frame: 0,
+        ClipMode::Loop
+    }
+}
+
+/// A sprite index in the sheet and how long it's shown, in seconds.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct Frame(pub usize, pub f32);
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Clip {
+    pub frames: Vec<Frame>,
+    #[serde(default)]
+    pub mode: ClipMode,
+    /// Playback rate, 2.0 plays the clip twice as fast.
+    #[serde(default = "default_speed")]
+    pub speed: f32,
+}
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+impl Clip {
+    fn duration(&self) -> f32 {
+        self.frames.iter().map(|frame| frame.1.max(0.0)).sum()
+    }
+}
+
+/// Every clip cut from one sprite sheet, read from a `.anim.ron` file next to
+/// the sheet.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct AnimationSet {
+    pub clips: HashMap<ClipName, Clip>,
+}
+
+impl AnimationSet {
+    /// Sheets without walk cycles fall back to their idle clip.
+    pub fn clip(&self, name: ClipName) -> Option<(ClipName, &Clip)> {
+        match self.clips.get(&name) {
+            Some(clip) => Some((name, clip)),
+            None if !name.is_action() && name != ClipName::Idle => self
+                .clips
+                .get(&ClipName::Idle)
+                .map(|clip| (ClipName::Idle, clip)),
+            None => None,
         }
     }
+
+    /// Whether `name` ever finishes, so playing it ends in an
+    /// `AnimationEvent`: a `Once` clip with some time in it.
+    pub fn finishes(&self, name: ClipName) -> bool {
+        self.clips.get(&name).map_or(false, |clip| {
+            clip.mode == ClipMode::Once && clip.duration() > 0.0
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct Animation {
+    pub set: Arc<AnimationSet>,
+    pub clip: ClipName,
+    pub frame: usize,
+    /// Seconds of the current frame already played. Whatever is left over
+    /// when a frame ends carries into the next one, so playback doesn't
+    /// drift with the frame rate.
+    pub elapsed: f32,
+    /// Multiplies the clip's own speed.
+    pub speed: f32,
+    pub paused: bool,
+    /// Plays clips from their last frame to their first.
+    pub reversed: bool,
+    // on the way back in a ping-pong clip
+    returning: bool,
+    /// Set when a `Once` clip reaches its last frame.
+    pub finished: bool,
 }
 
 impl Component for Animation {
@@ -26,14 +121,270 @@ impl Component for Animation {
 }
 
 impl Animation {
-    pub fn frame_update(&mut self, sprite_render: &mut SpriteRender, seconds: f32) {
-        if self.frame_life_time_count > 0.0 {
-            self.frame_life_time_count -= seconds;
+    pub fn new(set: Arc<AnimationSet>) -> Self {
+        Self {
+            set,
+            clip: ClipName::Idle,
+            frame: 0,
+            elapsed: 0.0,
+            speed: 1.0,
+            paused: false,
+            reversed: false,
+            returning: false,
+            finished: false,
+        }
+    }
+
+    /// Switches to `name` from its first frame. Playing the clip that's
+    /// already running, or one the set doesn't have, changes nothing.
+    pub fn play(&mut self, name: ClipName) {
+        let resolved = match self.set.clip(name) {
+            Some((resolved, _)) => resolved,
+            None => return,
+        };
+
+        if resolved != self.clip || self.finished {
+            self.clip = resolved;
+            self.restart();
+        }
+    }
+
+    /// Starts the current clip over, from its last frame if reversed.
+    pub fn restart(&mut self) {
+        let len = self
+            .set
+            .clips
+            .get(&self.clip)
+            .map_or(0, |clip| clip.frames.len());
+
+        self.frame = if self.reversed {
+            len.saturating_sub(1)
+        } else {
+            0
+        };
+        self.elapsed = 0.0;
+        self.returning = false;
+        self.finished = false;
+    }
+
+    /// True while a one-shot action is still running.
+    pub fn is_busy(&self) -> bool {
+        self.clip.is_action() && !self.finished
+    }
+
+    /// Sprite index of the current frame, if the clip has any frames.
+    pub fn sprite_number(&self) -> Option<usize> {
+        self.set
+            .clips
+            .get(&self.clip)
+            .and_then(|clip| clip.frames.get(self.frame))
+            .map(|frame| frame.0)
+    }
+
+    /// Plays `seconds` of the current clip. Returns true when a `Once` clip
+    /// finishes during this step.
+    pub fn advance(&mut self, seconds: f32) -> bool {
+        let set = self.set.clone();
+        let clip = match set.clips.get(&self.clip) {
+            Some(clip) => clip,
+            None => return false,
+        };
+
+        // a clip with no time in it can't move, and would never leave the
+        // loop below
+        if self.paused || self.finished || clip.duration() <= 0.0 {
+            return false;
+        }
+
+        self.frame = self.frame.min(clip.frames.len() - 1);
+        self.elapsed += seconds * clip.speed * self.speed;
+
+        while self.elapsed >= clip.frames[self.frame].1 {
+            self.elapsed -= clip.frames[self.frame].1.max(0.0);
+
+            if self.step(clip) {
+                self.finished = true;
+                self.elapsed = 0.0;
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Moves one frame along the clip. Returns true if a `Once` clip has no
+    /// frames left.
+    fn step(&mut self, clip: &Clip) -> bool {
+        let last = clip.frames.len() - 1;
+        let backwards = self.reversed != self.returning;
+        let at_end = if backwards {
+            self.frame == 0
         } else {
-            self.frame_life_time_count = self.max_count_till_next_frame;
-            self.current_frame = (self.current_frame + 1) % self.total_frames;
+            self.frame == last
+        };
+
+        if !at_end {
+            if backwards {
+                self.frame -= 1;
+            } else {
+                self.frame += 1;
+            }
+            return false;
+        }
+
+        match clip.mode {
+            ClipMode::Once => return true,
+            ClipMode::Loop => self.frame = if backwards { last } else { 0 },
+            ClipMode::PingPong if last > 0 => {
+                self.returning = !self.returning;
+                if backwards {
+                    self.frame += 1;
+                } else {
+                    self.frame -= 1;
+                }
+            }
+            ClipMode::PingPong => {}
+        }
+
+        false
+    }
+
+    /// Advances the current clip and writes its frame into `sprite_render`.
+    /// Returns true on the frame a `Once` clip finishes.
+    pub fn frame_update(&mut self, sprite_render: &mut SpriteRender, seconds: f32) -> bool {
+        let just_finished = self.advance(seconds);
+
+        if let Some(sprite_number) = self.sprite_number() {
+            sprite_render.sprite_number = sprite_number;
         }
 
-        sprite_render.sprite_number = self.current_frame;
+        just_finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn animation(mode: ClipMode, sprites: &[usize], seconds: f32) -> Animation {
+        let clip = Clip {
+            frames: sprites
+                .iter()
+                .map(|&sprite| Frame(sprite, seconds))
+                .collect(),
+            mode,
+            speed: 1.0,
+        };
+        let mut set = AnimationSet::default();
+        set.clips.insert(ClipName::Idle, clip);
+
+        Animation::new(Arc::new(set))
+    }
+
+    /// Steps `animation` by each delta and records the sprite shown after.
+    fn sprites(animation: &mut Animation, deltas: &[f32]) -> Vec<usize> {
+        deltas
+            .iter()
+            .map(|&delta| {
+                animation.advance(delta);
+                animation.sprite_number().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn leftover_time_carries_into_the_next_frame() {
+        let mut anim = animation(ClipMode::Loop, &[0, 1, 2, 3], 0.25);
+
+        // 0.375 + 0.375 = 0.75, which is three frames, not two
+        assert_eq!(sprites(&mut anim, &[0.375, 0.375]), vec![1, 3]);
+        assert_eq!(anim.elapsed, 0.0);
+    }
+
+    #[test]
+    fn same_time_lands_on_same_frame_at_any_frame_rate() {
+        let deltas = [0.0625; 23];
+        let total: f32 = deltas.iter().sum();
+
+        let mut small_steps = animation(ClipMode::Loop, &[0, 1, 2, 3, 4], 0.25);
+        let mut one_step = small_steps.clone();
+        let mut uneven = small_steps.clone();
+
+        sprites(&mut small_steps, &deltas);
+        sprites(&mut one_step, &[total]);
+        sprites(&mut uneven, &[0.5, 0.0078125, 0.75, 0.1796875]);
+
+        assert_eq!(small_steps.frame, 0);
+        assert_eq!(one_step.frame, 0);
+        assert_eq!(uneven.frame, 0);
+        assert_eq!(small_steps.elapsed, one_step.elapsed);
+    }
+
+    #[test]
+    fn loops_back_to_the_start() {
+        let mut anim = animation(ClipMode::Loop, &[5, 6, 7], 0.5);
+        assert_eq!(sprites(&mut anim, &[0.5; 4]), vec![6, 7, 5, 6]);
+    }
+
+    #[test]
+    fn once_finishes_a_single_time_and_holds_the_last_frame() {
+        let mut anim = animation(ClipMode::Once, &[0, 1, 2], 0.5);
+
+        assert!(!anim.advance(1.0));
+        assert!(anim.advance(0.5));
+        assert!(anim.finished);
+        assert!(!anim.advance(10.0));
+        assert_eq!(anim.sprite_number(), Some(2));
+    }
+
+    #[test]
+    fn ping_pong_turns_around_at_both_ends() {
+        let mut anim = animation(ClipMode::PingPong, &[0, 1, 2], 0.5);
+        assert_eq!(sprites(&mut anim, &[0.5; 6]), vec![1, 2, 1, 0, 1, 2]);
+    }
+
+    #[test]
+    fn reversed_plays_from_the_last_frame() {
+        let mut anim = animation(ClipMode::Loop, &[0, 1, 2], 0.5);
+        anim.reversed = true;
+        anim.restart();
+
+        assert_eq!(anim.sprite_number(), Some(2));
+        assert_eq!(sprites(&mut anim, &[0.5; 3]), vec![1, 0, 2]);
+    }
+
+    #[test]
+    fn paused_and_speed() {
+        let mut anim = animation(ClipMode::Loop, &[0, 1, 2, 3], 0.5);
+
+        anim.paused = true;
+        assert_eq!(sprites(&mut anim, &[1.0]), vec![0]);
+
+        anim.paused = false;
+        anim.speed = 2.0;
+        assert_eq!(sprites(&mut anim, &[0.25, 0.5]), vec![1, 3]);
+    }
+
+    #[test]
+    fn empty_and_zero_length_clips_do_not_panic() {
+        let mut empty = animation(ClipMode::Loop, &[], 0.5);
+        assert!(!empty.advance(1.0));
+        assert_eq!(empty.sprite_number(), None);
+
+        let mut instant = animation(ClipMode::Loop, &[0, 1], 0.0);
+        assert!(!instant.advance(1.0));
+        assert_eq!(instant.sprite_number(), Some(0));
+    }
+
+    #[test]
+    fn only_timed_once_clips_finish() {
+        let once = animation(ClipMode::Once, &[0, 1], 0.5);
+        let looping = animation(ClipMode::Loop, &[0, 1], 0.5);
+        let instant = animation(ClipMode::Once, &[0, 1], 0.0);
+
+        assert!(once.set.finishes(ClipName::Idle));
+        assert!(!once.set.finishes(ClipName::Die));
+        assert!(!looping.set.finishes(ClipName::Idle));
+        assert!(!instant.set.finishes(ClipName::Idle));
     }
 }
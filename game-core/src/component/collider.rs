@@ -0,0 +1,93 @@
+use amethyst::{
+    core::cgmath::Vector2,
+    ecs::{Component, DenseVecStorage},
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Shape {
+    Circle { radius: f32 },
+    Aabb { half_width: f32, half_height: f32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Collider {
+    pub shape: Shape,
+}
+
+impl Component for Collider {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Collider {
+    pub fn circle(radius: f32) -> Self {
+        Self {
+            shape: Shape::Circle { radius },
+        }
+    }
+
+    pub fn aabb(width: f32, height: f32) -> Self {
+        Self {
+            shape: Shape::Aabb {
+                half_width: width / 2.0,
+                half_height: height / 2.0,
+            },
+        }
+    }
+
+    /// Circle inscribed in a sprite of the given pixel size, after scaling.
+    pub fn from_sprite(width: f32, height: f32, scale: f32) -> Self {
+        Self::circle(width.min(height) * scale / 2.0)
+    }
+
+    pub fn half_extents(&self) -> Vector2<f32> {
+        match self.shape {
+            Shape::Circle { radius } => Vector2 {
+                x: radius,
+                y: radius,
+            },
+            Shape::Aabb {
+                half_width,
+                half_height,
+            } => Vector2 {
+                x: half_width,
+                y: half_height,
+            },
+        }
+    }
+
+    pub fn overlaps(&self, pos: Vector2<f32>, other: &Collider, other_pos: Vector2<f32>) -> bool {
+        match (self.shape, other.shape) {
+            (Shape::Circle { radius: r1 }, Shape::Circle { radius: r2 }) => {
+                let d = other_pos - pos;
+                d.x * d.x + d.y * d.y <= (r1 + r2) * (r1 + r2)
+            }
+            (Shape::Aabb { .. }, Shape::Aabb { .. }) => {
+                let e1 = self.half_extents();
+                let e2 = other.half_extents();
+                (pos.x - other_pos.x).abs() <= e1.x + e2.x
+                    && (pos.y - other_pos.y).abs() <= e1.y + e2.y
+            }
+            (Shape::Circle { radius }, Shape::Aabb { .. }) => {
+                circle_aabb(pos, radius, other_pos, other.half_extents())
+            }
+            (Shape::Aabb { .. }, Shape::Circle { radius }) => {
+                circle_aabb(other_pos, radius, pos, self.half_extents())
+            }
+        }
+    }
+}
+
+fn circle_aabb(
+    center: Vector2<f32>,
+    radius: f32,
+    box_pos: Vector2<f32>,
+    half: Vector2<f32>,
+) -> bool {
+    // closest point on the box to the circle's center
+    let closest_x = center.x.max(box_pos.x - half.x).min(box_pos.x + half.x);
+    let closest_y = center.y.max(box_pos.y - half.y).min(box_pos.y + half.y);
+    let dx = center.x - closest_x;
+    let dy = center.y - closest_y;
+
+    dx * dx + dy * dy <= radius * radius
+}
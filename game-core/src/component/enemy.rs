@@ -1,15 +1,24 @@
 use amethyst::ecs::{Component, DenseVecStorage};
+use config::{AttackPattern, EnemyArchetype};
 
 pub struct Enemy {
-    pub hp: u32,
-    pub has_player_in_sight: bool,
+    pub idle_speed: f32,
+    pub tracking_speed: f32,
+    pub detect_radius: f32,
+    pub attack_range: f32,
+    pub flee_below: f32, // fraction of max hp
+    pub attack: AttackPattern,
 }
 
 impl Default for Enemy {
     fn default() -> Self {
         Self {
-            hp: 120,
-            has_player_in_sight: false,
+            idle_speed: 50.0,
+            tracking_speed: 100.0,
+            detect_radius: 180.0,
+            attack_range: 180.0,
+            flee_below: 0.0,
+            attack: AttackPattern::Forward,
         }
     }
 }
@@ -17,3 +26,16 @@ impl Default for Enemy {
 impl Component for Enemy {
     type Storage = DenseVecStorage<Self>;
 }
+
+impl Enemy {
+    pub fn from_archetype(archetype: &EnemyArchetype) -> Self {
+        Self {
+            idle_speed: archetype.idle_speed,
+            tracking_speed: archetype.tracking_speed,
+            detect_radius: archetype.detect_radius,
+            attack_range: archetype.attack_range,
+            flee_below: archetype.flee_below,
+            attack: archetype.attack,
+        }
+    }
+}
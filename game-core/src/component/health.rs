@@ -0,0 +1,84 @@
+use amethyst::ecs::{Component, DenseVecStorage};
+
+pub struct Health {
+    pub hp: u32,
+    pub max_hp: u32,
+    pub invulnerability: f32, // seconds of invulnerability after taking damage
+    pub invulnerable_for: f32, // seconds left
+    pub regen: f32,           // hp per second
+    pub regen_progress: f32,
+    pub dead: bool,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self::new(10)
+    }
+}
+
+impl Component for Health {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Health {
+    pub fn new(max_hp: u32) -> Self {
+        Self {
+            hp: max_hp,
+            max_hp,
+            invulnerability: 0.0,
+            invulnerable_for: 0.0,
+            regen: 0.0,
+            regen_progress: 0.0,
+            dead: false,
+        }
+    }
+
+    pub fn with_invulnerability(mut self, seconds: f32) -> Self {
+        self.invulnerability = seconds;
+        self
+    }
+
+    pub fn with_regen(mut self, hp_per_second: f32) -> Self {
+        self.regen = hp_per_second;
+        self
+    }
+
+    /// Returns false if the damage was ignored because of invulnerability
+    /// frames or because the entity is already dead.
+    pub fn damage(&mut self, amount: u32) -> bool {
+        if self.dead || self.invulnerable_for > 0.0 {
+            return false;
+        }
+
+        self.hp = self.hp.saturating_sub(amount);
+        self.invulnerable_for = self.invulnerability;
+        self.regen_progress = 0.0;
+        true
+    }
+
+    pub fn heal(&mut self, amount: u32) {
+        if !self.dead {
+            self.hp = (self.hp + amount).min(self.max_hp);
+        }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.dead || self.hp == 0
+    }
+
+    pub fn tick(&mut self, seconds: f32) {
+        self.invulnerable_for = (self.invulnerable_for - seconds).max(0.0);
+
+        if self.dead || self.regen <= 0.0 || self.hp >= self.max_hp {
+            self.regen_progress = 0.0;
+            return;
+        }
+
+        self.regen_progress += self.regen * seconds;
+        let whole = self.regen_progress.floor();
+        if whole >= 1.0 {
+            self.regen_progress -= whole;
+            self.heal(whole as u32);
+        }
+    }
+}
@@ -1,13 +1,23 @@
+pub mod ai;
 pub mod ally;
 pub mod animation;
+pub mod collider;
 pub mod enemy;
+pub mod health;
 pub mod motion;
+pub mod path;
 pub mod player;
 pub mod projectile;
+pub mod weapon;
 
+pub use self::ai::{Ai, AiState};
 pub use self::ally::Ally;
-pub use self::animation::Animation;
+pub use self::animation::{Animation, AnimationSet, ClipName};
+pub use self::collider::Collider;
 pub use self::enemy::Enemy;
+pub use self::health::Health;
 pub use self::motion::Motion;
+pub use self::path::Path;
 pub use self::player::Player;
-pub use self::projectile::Projectile;
+pub use self::projectile::{Projectile, Team};
+pub use self::weapon::Weapon;
@@ -8,6 +8,8 @@ pub struct Motion {
     pub acc: Vector2<f32>,
     pub max_vel: Option<f32>,
     pub min_vel: Option<f32>,
+    /// Reflect off impassable tiles instead of stopping against them.
+    pub bounce: bool,
 }
 
 impl Default for Motion {
@@ -17,6 +19,7 @@ impl Default for Motion {
             acc: Vector2 { x: 0.0, y: 0.0 },
             max_vel: None,
             min_vel: None,
+            bounce: false,
         }
     }
 }
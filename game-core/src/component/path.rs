@@ -0,0 +1,20 @@
+use amethyst::ecs::{Component, DenseVecStorage};
+use crate::pathfinding::Tile;
+
+/// Cached route from `pathfinding::next_waypoint`, next tile last.
+#[derive(Default)]
+pub struct Path {
+    pub tiles: Vec<Tile>,
+    pub goal: Option<Tile>,
+}
+
+impl Component for Path {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Path {
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+        self.goal = None;
+    }
+}
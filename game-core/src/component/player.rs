@@ -6,21 +6,21 @@ use amethyst::{
     prelude::*,
     renderer::{SpriteRender, SpriteSheetHandle, Transparent},
 };
-use crate::component::Animation;
+use crate::component::{Animation, Collider, Health, Motion, Weapon};
 
 #[derive(Debug)]
 pub struct Player {
-    pub hp: u32,
     pub num_allies: u32,
     pub last_direction: Vector2<f32>,
+    pub moving: bool,
 }
 
 impl Default for Player {
     fn default() -> Self {
         Self {
-            hp: 10,
             num_allies: 0,
             last_direction: Vector2 { x: 1.0, y: 1.0 },
+            moving: false,
         }
     }
 }
@@ -30,10 +30,16 @@ impl Component for Player {
 }
 
 impl Player {
-    pub fn new(world: &mut World, sprite_sheet: &SpriteSheetHandle) -> Entity {
+    pub fn new(
+        world: &mut World,
+        sprite_sheet: &SpriteSheetHandle,
+        animation: Animation,
+        weapon: Weapon,
+        position: Vector2<f32>,
+    ) -> Entity {
         let mut transform = Transform::default();
-        transform.translation.x = 32.0 * 70.0;
-        transform.translation.y = 32.0 * 50.0;
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
 
         let sprite = SpriteRender {
             sprite_sheet: sprite_sheet.clone(),
@@ -42,20 +48,20 @@ impl Player {
             flip_vertical: false,
         };
 
-        let anim = Animation {
-            total_frames: 8,
-            max_count_till_next_frame: 0.5,
-            frame_life_time_count: 0.5,
-            current_frame: 0,
-        };
-
         world
             .create_entity()
             .with(transform)
             .with(Player::default())
+            .with(Motion::default())
             .with(sprite)
             .with(Transparent)
-            .with(anim)
-            .build()
+            .with(animation)
+            .with(weapon)
+            .with(Collider::from_sprite(32.0, 32.0, 1.0))
+            .with(
+                Health::new(10)
+                    .with_invulnerability(1.0)
+                    .with_regen(0.2),
+            ).build()
     }
 }
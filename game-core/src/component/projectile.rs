@@ -1,13 +1,35 @@
-use amethyst::ecs::{Component, NullStorage};
+use amethyst::core::cgmath::Vector2;
+use amethyst::ecs::{Component, DenseVecStorage};
 
-pub struct Projectile;
+/// Which side fired a projectile. Projectiles never hit their own side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Team {
+    Player,
+    Enemy,
+}
+
+pub struct Projectile {
+    pub team: Team,
+    pub damage: u32,
+    /// Seconds left before it's removed.
+    pub lifetime: f32,
+    /// Where it was fired from, and how far from there it may get.
+    pub origin: Vector2<f32>,
+    pub range: Option<f32>,
+}
 
 impl Default for Projectile {
     fn default() -> Self {
-        Projectile
+        Self {
+            team: Team::Player,
+            damage: 1,
+            lifetime: 3.0,
+            origin: Vector2 { x: 0.0, y: 0.0 },
+            range: None,
+        }
     }
 }
 
 impl Component for Projectile {
-    type Storage = NullStorage<Self>;
+    type Storage = DenseVecStorage<Self>;
 }
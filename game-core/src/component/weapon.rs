@@ -0,0 +1,188 @@
+use amethyst::core::cgmath::Vector2;
+use amethyst::ecs::{Component, DenseVecStorage};
+use config::WeaponDef;
+use crate::component::Team;
+
+/// Something that shoots. The attack systems only pull the trigger,
+/// `weapon::Fire` keeps the rate of fire and spawns the projectiles.
+pub struct Weapon {
+    pub def: WeaponDef,
+    pub team: Team,
+    /// Where to shoot this frame, if the trigger is held.
+    pub trigger: Option<Vector2<f32>>,
+    // time until the next shot can go
+    cooldown: f32,
+    // shots left in the current burst, all along `aim`
+    burst_left: u32,
+    aim: Vector2<f32>,
+}
+
+impl Component for Weapon {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Weapon {
+    pub fn new(def: WeaponDef, team: Team) -> Self {
+        Self {
+            def,
+            team,
+            trigger: None,
+            cooldown: 0.0,
+            burst_left: 0,
+            aim: Vector2 { x: 0.0, y: 0.0 },
+        }
+    }
+
+    /// Counts the cooldown down and releases the trigger. Returns the
+    /// direction of the shot fired this frame, if there is one.
+    pub fn update(&mut self, seconds: f32) -> Option<Vector2<f32>> {
+        let trigger = self.trigger.take();
+        if self.cooldown > 0.0 {
+            self.cooldown -= seconds;
+            if self.cooldown > 0.0 {
+                return None;
+            }
+        }
+
+        if self.burst_left == 0 {
+            match trigger {
+                Some(direction) => {
+                    self.aim = direction;
+                    self.burst_left = self.def.burst.max(1);
+                }
+                None => {
+                    // time spent idle doesn't buy faster shots later
+                    self.cooldown = 0.0;
+                    return None;
+                }
+            }
+        }
+
+        // adding keeps whatever the last frame overshot by, so the rate
+        // stays steady when frames don't line up with it
+        self.burst_left -= 1;
+        self.cooldown += if self.burst_left > 0 {
+            self.def.burst_interval
+        } else {
+            self.def.cooldown
+        };
+
+        Some(self.aim)
+    }
+
+    /// Turns for each projectile of a shot, in degrees, before jitter.
+    pub fn pattern(&self) -> Vec<f32> {
+        let count = self.def.projectiles.max(1);
+        if count == 1 {
+            return vec![0.0];
+        }
+
+        let step = self.def.spread / (count - 1) as f32;
+        (0..count)
+            .map(|i| i as f32 * step - self.def.spread / 2.0)
+            .collect()
+    }
+}
+
+/// Turns `v` anticlockwise by `degrees`.
+pub fn rotate(v: Vector2<f32>, degrees: f32) -> Vector2<f32> {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    Vector2 {
+        x: v.x * cos - v.y * sin,
+        y: v.x * sin + v.y * cos,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weapon(cooldown: f32, burst: u32, burst_interval: f32) -> Weapon {
+        let def = WeaponDef {
+            name: "test".into(),
+            cooldown,
+            burst,
+            burst_interval,
+            projectiles: 1,
+            spread: 0.0,
+            jitter: 0.0,
+            speed: 100.0,
+            drag: 0.0,
+            lifetime: 1.0,
+            range: None,
+            damage: 1,
+            sheet: "bubble".into(),
+            animation: "bubble".into(),
+        };
+
+        Weapon::new(def, Team::Player)
+    }
+
+    /// Holds the trigger for each delta and counts the shots.
+    fn shots(weapon: &mut Weapon, deltas: &[f32]) -> usize {
+        deltas
+            .iter()
+            .filter(|&&delta| {
+                weapon.trigger = Some(Vector2 { x: 1.0, y: 0.0 });
+                weapon.update(delta).is_some()
+            })
+            .count()
+    }
+
+    #[test]
+    fn fires_at_the_cooldown_rate_at_any_frame_rate() {
+        let mut fast = weapon(0.25, 1, 0.0);
+        let mut slow = weapon(0.25, 1, 0.0);
+
+        // the first shot goes straight away, then one every 0.25
+        assert_eq!(shots(&mut fast, &[0.0625; 32]), 8);
+        assert_eq!(shots(&mut slow, &[0.125; 16]), 8);
+    }
+
+    #[test]
+    fn needs_the_trigger() {
+        let mut weapon = weapon(0.25, 1, 0.0);
+
+        assert_eq!(weapon.update(1.0), None);
+        assert_eq!(shots(&mut weapon, &[0.0]), 1);
+        // released, then pulled again before the cooldown is over
+        assert_eq!(weapon.update(0.1), None);
+        assert_eq!(shots(&mut weapon, &[0.1]), 0);
+    }
+
+    #[test]
+    fn idle_time_does_not_bank_shots() {
+        let mut weapon = weapon(0.25, 1, 0.0);
+
+        assert_eq!(shots(&mut weapon, &[0.0]), 1);
+        for _ in 0..10 {
+            weapon.update(1.0);
+        }
+        assert_eq!(shots(&mut weapon, &[0.0, 0.0, 0.0]), 1);
+    }
+
+    #[test]
+    fn bursts_finish_along_the_first_aim_without_the_trigger() {
+        let mut weapon = weapon(1.0, 3, 0.1);
+        weapon.trigger = Some(Vector2 { x: 0.0, y: 1.0 });
+
+        assert_eq!(weapon.update(0.0), Some(Vector2 { x: 0.0, y: 1.0 }));
+        assert_eq!(weapon.update(0.1), Some(Vector2 { x: 0.0, y: 1.0 }));
+        assert_eq!(weapon.update(0.1), Some(Vector2 { x: 0.0, y: 1.0 }));
+        assert_eq!(weapon.update(0.1), None);
+        // the full cooldown comes after the burst
+        assert_eq!(shots(&mut weapon, &[0.5, 0.5, 0.1]), 1);
+    }
+
+    #[test]
+    fn spreads_projectiles_evenly() {
+        let mut shotgun = weapon(1.0, 1, 0.0);
+        shotgun.def.projectiles = 5;
+        shotgun.def.spread = 40.0;
+
+        assert_eq!(shotgun.pattern(), vec![-20.0, -10.0, 0.0, 10.0, 20.0]);
+
+        shotgun.def.projectiles = 1;
+        assert_eq!(shotgun.pattern(), vec![0.0]);
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Ally {
@@ -17,10 +18,65 @@ impl Default for Ally {
     }
 }
 
+/// How the player moves. Speeds are in pixels per second, so movement feels
+/// the same at any frame rate.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlayerMovement {
+    pub max_speed: f32,
+    /// Speed gained per second while a direction is held.
+    pub acceleration: f32,
+    /// Speed lost per second once it's released.
+    pub friction: f32,
+    /// Keeps diagonals from being faster than moving straight.
+    pub normalize_diagonal: bool,
+}
+
+impl Default for PlayerMovement {
+    fn default() -> Self {
+        PlayerMovement {
+            max_speed: 300.0,
+            acceleration: 3000.0,
+            friction: 2400.0,
+            normalize_diagonal: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Level {
+    pub name: String,
+    /// Tiled map, relative to the resources directory.
+    pub map: String,
+    /// Entry used when the level is started rather than entered through an exit.
+    pub default_entry: String,
+    /// Entry points in tile coordinates. A `player_start` object in the map
+    /// with the same name takes precedence.
+    pub entries: HashMap<String, (f32, f32)>,
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        let mut entries = HashMap::new();
+        entries.insert("start".into(), (70.0, 50.0));
+
+        Level {
+            name: "desert".into(),
+            map: "testmap.tmx".into(),
+            default_entry: "start".into(),
+            entries,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct GameoffConfig {
     pub ally: Ally,
     pub speed: f32,
+    pub player: PlayerMovement,
+    /// Name of the player's weapon in `weapons.ron`.
+    pub player_weapon: String,
+    /// The first level is where a new game starts.
+    pub levels: Vec<Level>,
 }
 
 impl Default for GameoffConfig {
@@ -28,6 +84,92 @@ impl Default for GameoffConfig {
         GameoffConfig {
             speed: 0.0,
             ally: Ally::default(),
+            player: PlayerMovement::default(),
+            player_weapon: "bubbles".into(),
+            levels: vec![Level::default()],
         }
     }
 }
+
+impl GameoffConfig {
+    pub fn level(&self, name: &str) -> Option<&Level> {
+        self.levels.iter().find(|level| level.name == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum AttackPattern {
+    /// Never shoots.
+    Passive,
+    /// Shoots along its own heading while chasing.
+    Forward,
+    /// Shoots straight at the player.
+    Aimed,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EnemyArchetype {
+    pub name: String,
+    /// Ids of the `Enemy` sheet and animation in `assets.ron`.
+    pub sheet: String,
+    pub animation: String,
+    pub hp: u32,
+    pub idle_speed: f32,
+    pub tracking_speed: f32,
+    pub detect_radius: f32,
+    pub attack_range: f32,
+    /// Runs away once hp drops below this fraction of max hp.
+    pub flee_below: f32,
+    pub attack: AttackPattern,
+    /// Name of its weapon in `weapons.ron`, if it has one.
+    pub weapon: Option<String>,
+    /// Relative spawn chance against the other archetypes.
+    pub weight: u32,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct EnemyArchetypes {
+    pub archetypes: Vec<EnemyArchetype>,
+}
+
+/// A weapon from `weapons.ron`. Times are in seconds, speeds in pixels per
+/// second and angles in degrees.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WeaponDef {
+    pub name: String,
+    /// Time between trigger pulls that fire.
+    pub cooldown: f32,
+    /// Shots fired by one pull, `burst_interval` apart.
+    pub burst: u32,
+    pub burst_interval: f32,
+    /// Projectiles per shot, fanned out evenly across `spread`.
+    pub projectiles: u32,
+    pub spread: f32,
+    /// Random turn of each projectile, up to this far either way.
+    pub jitter: f32,
+    pub speed: f32,
+    /// Fraction of its starting speed a projectile loses per second, until
+    /// it's down to a slow drift.
+    pub drag: f32,
+    /// How long a projectile lasts.
+    pub lifetime: f32,
+    /// How far from where it was fired a projectile may get, if there's a
+    /// limit.
+    #[serde(default)]
+    pub range: Option<f32>,
+    pub damage: u32,
+    /// Ids of the `Projectile` sheet and animation in `assets.ron`.
+    pub sheet: String,
+    pub animation: String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Weapons {
+    pub weapons: Vec<WeaponDef>,
+}
+
+impl Weapons {
+    pub fn get(&self, name: &str) -> Option<&WeaponDef> {
+        self.weapons.iter().find(|weapon| weapon.name == name)
+    }
+}
@@ -0,0 +1,186 @@
+use amethyst::{
+    core::cgmath::{InnerSpace, Vector2},
+    input::{Axis, Bindings, Button},
+    prelude::*,
+    renderer::VirtualKeyCode,
+    utils::application_root_dir,
+};
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Shipped with the game.
+const DEFAULT_FILE: &str = "input.ron";
+/// Written when the player rebinds anything, and read instead of the
+/// shipped file from then on.
+const USER_FILE: &str = "user_input.ron";
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct KeyboardBindings {
+    pub up: VirtualKeyCode,
+    pub down: VirtualKeyCode,
+    pub left: VirtualKeyCode,
+    pub right: VirtualKeyCode,
+    pub fire: VirtualKeyCode,
+}
+
+impl Default for KeyboardBindings {
+    fn default() -> Self {
+        KeyboardBindings {
+            up: VirtualKeyCode::W,
+            down: VirtualKeyCode::S,
+            left: VirtualKeyCode::A,
+            right: VirtualKeyCode::D,
+            fire: VirtualKeyCode::Space,
+        }
+    }
+}
+
+impl KeyboardBindings {
+    /// The `entity_x`/`entity_y` axes and `fire` action for amethyst's
+    /// `InputHandler`.
+    pub fn bindings(&self) -> Bindings<String, String> {
+        let mut bindings = Bindings::new();
+        bindings.insert_axis(
+            "entity_x",
+            Axis::Emulated {
+                pos: Button::Key(self.right),
+                neg: Button::Key(self.left),
+            },
+        );
+        bindings.insert_axis(
+            "entity_y",
+            Axis::Emulated {
+                pos: Button::Key(self.up),
+                neg: Button::Key(self.down),
+            },
+        );
+        bindings.insert_action_binding("fire", Button::Key(self.fire));
+        bindings
+    }
+}
+
+/// Twin-stick layout: one stick moves, the other aims and fires.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GamepadBindings {
+    pub move_x: gilrs::Axis,
+    pub move_y: gilrs::Axis,
+    pub aim_x: gilrs::Axis,
+    pub aim_y: gilrs::Axis,
+    /// Fires along the aim stick, or the way the player last moved.
+    pub fire: gilrs::Button,
+    /// How far a stick has to be pushed before it counts, from 0 to 1.
+    pub dead_zone: f32,
+}
+
+impl Default for GamepadBindings {
+    fn default() -> Self {
+        GamepadBindings {
+            move_x: gilrs::Axis::LeftStickX,
+            move_y: gilrs::Axis::LeftStickY,
+            aim_x: gilrs::Axis::RightStickX,
+            aim_y: gilrs::Axis::RightStickY,
+            fire: gilrs::Button::RightTrigger2,
+            dead_zone: 0.25,
+        }
+    }
+}
+
+impl GamepadBindings {
+    /// Zeroes a stick inside the dead zone and rescales the rest, so it
+    /// starts from 0 at the edge of the dead zone instead of jumping.
+    pub fn stick(&self, x: f32, y: f32) -> Vector2<f32> {
+        let stick = Vector2 { x, y };
+        let magnitude = stick.magnitude().min(1.0);
+        if magnitude <= self.dead_zone {
+            return Vector2 { x: 0.0, y: 0.0 };
+        }
+
+        stick.normalize_to((magnitude - self.dead_zone) / (1.0 - self.dead_zone))
+    }
+}
+
+/// Where bubbles go when fired from the keyboard. The gamepad's aim stick
+/// takes over whenever it's pushed, whatever this is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum AimMode {
+    /// The way the player last walked.
+    Movement,
+    /// Towards the mouse cursor. The left mouse button fires too.
+    Mouse,
+}
+
+impl Default for AimMode {
+    fn default() -> Self {
+        AimMode::Movement
+    }
+}
+
+impl AimMode {
+    pub fn next(self) -> Self {
+        match self {
+            AimMode::Movement => AimMode::Mouse,
+            AimMode::Mouse => AimMode::Movement,
+        }
+    }
+}
+
+/// Every control the player can rebind, see `state::Rebind`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Controls {
+    pub keyboard: KeyboardBindings,
+    pub gamepad: GamepadBindings,
+    // files saved before there was a choice keep loading
+    #[serde(default)]
+    pub aim: AimMode,
+}
+
+fn resource_path(file: &str) -> String {
+    format!("{}/resources/{}", application_root_dir(), file)
+}
+
+impl Controls {
+    /// The player's own bindings if they have any, otherwise the shipped
+    /// ones.
+    pub fn read() -> Self {
+        let user = resource_path(USER_FILE);
+        if Path::new(&user).exists() {
+            match Controls::load_no_fallback(&user) {
+                Ok(controls) => return controls,
+                Err(e) => warn!("ignoring {}: {}", user, e),
+            }
+        }
+
+        Controls::load(resource_path(DEFAULT_FILE))
+    }
+
+    /// Writes the bindings to the user file.
+    pub fn save(&self) -> Result<()> {
+        let path = resource_path(USER_FILE);
+        match self.write(&path) {
+            Ok(()) => Ok(()),
+            Err(cause) => Err(Error::Config { path, cause }),
+        }
+    }
+}
+
+/// What the gamepad is doing this frame, filled in by `system::gamepad::Poll`.
+#[derive(Debug)]
+pub struct GamepadState {
+    pub movement: Vector2<f32>,
+    pub aim: Vector2<f32>,
+    pub fire: bool,
+    /// Buttons pressed since the last frame, for rebinding.
+    pub pressed: Vec<gilrs::Button>,
+}
+
+impl Default for GamepadState {
+    fn default() -> Self {
+        GamepadState {
+            movement: Vector2 { x: 0.0, y: 0.0 },
+            aim: Vector2 { x: 0.0, y: 0.0 },
+            fire: false,
+            pressed: vec![],
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use amethyst::config::ConfigError;
+use assets::{AnimationId, SheetId};
+use std::{error, fmt, result};
+
+pub type Result<T> = result::Result<T, Error>;
+
+/// Everything that can go wrong while loading assets, maps and levels.
+#[derive(Debug)]
+pub enum Error {
+    /// The asset manifest or one of the files it lists couldn't be read.
+    Config { path: String, cause: ConfigError },
+    /// A Tiled map (or one of its external tilesets) couldn't be parsed.
+    Map {
+        path: String,
+        cause: tiled::TiledError,
+    },
+    /// A tileset listed in the manifest couldn't be read.
+    Tileset {
+        path: String,
+        cause: tilesheet::Error,
+    },
+    /// Some assets failed to load, each one is logged as it's found.
+    FailedAssets(usize),
+    /// The manifest has no sprite sheet with this id.
+    UnknownSheet(SheetId),
+    /// A map uses a tileset that isn't in the manifest.
+    UnknownTileset(String),
+    /// The manifest has no animation with this id.
+    UnknownAnimation(AnimationId),
+    /// An exit or the config refers to a level that isn't in `config.ron`.
+    UnknownLevel(String),
+    /// The config or an enemy archetype refers to a weapon that isn't in
+    /// `weapons.ron`.
+    UnknownWeapon(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Config { path, cause } => write!(f, "failed to read {}: {}", path, cause),
+            Error::Map { path, cause } => write!(f, "failed to load map {}: {}", path, cause),
+            Error::Tileset { path, cause } => {
+                write!(f, "failed to load tileset {}: {}", path, cause)
+            }
+            Error::FailedAssets(count) => write!(f, "{} assets failed to load", count),
+            Error::UnknownSheet(id) => write!(f, "no sprite sheet {:?} in the manifest", id),
+            Error::UnknownTileset(name) => write!(f, "no tileset {:?} in the manifest", name),
+            Error::UnknownAnimation(id) => write!(f, "no animation {:?} in the manifest", id),
+            Error::UnknownLevel(name) => write!(f, "unknown level {:?}", name),
+            Error::UnknownWeapon(name) => write!(f, "no weapon {:?} in weapons.ron", name),
+        }
+    }
+}
+
+impl error::Error for Error {}
@@ -0,0 +1,142 @@
+use amethyst::{
+    core::cgmath::Vector2,
+    core::Transform,
+    ecs::prelude::*,
+    prelude::*,
+    renderer::Camera,
+};
+use component::{Ally, Motion, Path, Player};
+use config::{GameoffConfig, Level};
+use crate::assets::{AnimationId, Assets, SheetId};
+use crate::error::{Error, Result};
+use crate::map::{self, MapSpawns, TILE_SIZE};
+
+pub struct CurrentLevel {
+    pub name: String,
+}
+
+pub struct LevelTarget {
+    pub level: String,
+    pub entry: Option<String>,
+}
+
+/// Set by `system::level::Exits`, carried out by the `Game` state between
+/// frames.
+#[derive(Default)]
+pub struct LevelChange {
+    pub target: Option<LevelTarget>,
+}
+
+/// Loads the level's map and pickups and returns where the player should be
+/// placed.
+pub fn load(world: &mut World, level: &Level, entry: Option<&str>) -> Result<Vector2<f32>> {
+    map::load_map_sprites(world, &level.map)?;
+    world.add_resource(CurrentLevel {
+        name: level.name.clone(),
+    });
+
+    let (sprite_sheet, animation) = {
+        let assets = world.read_resource::<Assets>();
+        (
+            assets.sheet(&SheetId::Player)?,
+            assets.animation(&AnimationId::Ally)?,
+        )
+    };
+    let pickups = world.read_resource::<MapSpawns>().ally_pickups.clone();
+    for position in pickups {
+        Ally::new(world, &sprite_sheet, animation.clone(), position);
+    }
+
+    Ok(entry_position(
+        world,
+        level,
+        entry.unwrap_or(&level.default_entry),
+    ))
+}
+
+fn entry_position(world: &World, level: &Level, entry: &str) -> Vector2<f32> {
+    let spawns = world.read_resource::<MapSpawns>();
+
+    if let Some(position) = spawns.player_starts.get(entry) {
+        return *position;
+    }
+
+    if let Some((x, y)) = level.entries.get(entry) {
+        return Vector2 {
+            x: x * TILE_SIZE,
+            y: y * TILE_SIZE,
+        };
+    }
+
+    warn!("level {:?} has no entry {:?}", level.name, entry);
+    spawns
+        .player_starts
+        .values()
+        .next()
+        .cloned()
+        .unwrap_or(Vector2 { x: 0.0, y: 0.0 })
+}
+
+/// Deletes everything belonging to the current level. The player, the camera
+/// and recruited allies (the ones following the player) are kept.
+pub fn unload(world: &mut World) {
+    {
+        let entities = world.entities();
+        let players = world.read_storage::<Player>();
+        let allies = world.read_storage::<Ally>();
+        let motions = world.read_storage::<Motion>();
+        let cameras = world.read_storage::<Camera>();
+
+        for entity in (&*entities).join() {
+            let carried = players.contains(entity)
+                || cameras.contains(entity)
+                || (allies.contains(entity) && motions.contains(entity));
+
+            if !carried {
+                let _ = entities.delete(entity);
+            }
+        }
+    }
+
+    world.maintain();
+}
+
+/// Swaps the current level for `target`, bringing the player and their
+/// recruited allies along to the entry point.
+pub fn change(world: &mut World, target: &LevelTarget) -> Result<()> {
+    let level = world
+        .read_resource::<GameoffConfig>()
+        .level(&target.level)
+        .cloned()
+        .ok_or_else(|| Error::UnknownLevel(target.level.clone()))?;
+
+    info!("entering {}", level.name);
+    unload(world);
+    let entry = load(world, &level, target.entry.as_ref().map(|e| e.as_str()))?;
+
+    let players = world.read_storage::<Player>();
+    let allies = world.read_storage::<Ally>();
+    let cameras = world.read_storage::<Camera>();
+    let mut transforms = world.write_storage::<Transform>();
+    let mut paths = world.write_storage::<Path>();
+
+    for (_, transform) in (&players, &mut transforms).join() {
+        transform.translation.x = entry.x;
+        transform.translation.y = entry.y;
+    }
+
+    // allies were following the player, so drop them right on top and let
+    // the flocking spread them out again
+    for (_, transform, path) in (&allies, &mut transforms, &mut paths).join() {
+        transform.translation.x = entry.x;
+        transform.translation.y = entry.y;
+        path.clear();
+    }
+
+    for (_, transform) in (&cameras, &mut transforms).join() {
+        transform.translation.x = entry.x - transform.scale.x / 2.0;
+        transform.translation.y = entry.y - transform.scale.y / 2.0;
+    }
+
+    Ok(())
+}
@@ -3,13 +3,22 @@
     allow(clippy::type_complexity, clippy::new_ret_no_self)
 )]
 extern crate amethyst;
+extern crate gilrs;
+#[macro_use]
+extern crate log;
 extern crate rand;
 extern crate serde;
+extern crate tilesheet;
 
+mod assets;
 mod component;
 pub mod config;
+mod controls;
+mod error;
+mod level;
 mod load;
 mod map;
+mod pathfinding;
 mod state;
 mod system;
 
@@ -22,12 +31,15 @@ use amethyst::{
     },
     utils::application_root_dir,
 };
-use state::Menu;
+use state::Loading;
 
 pub fn run() -> amethyst::Result<()> {
     let root = format!("{}/resources", application_root_dir());
     let display_config = DisplayConfig::load(format!("{}/display_config.ron", root));
     let gameoff_config = config::GameoffConfig::load(format!("{}/config.ron", root));
+    let enemy_archetypes = config::EnemyArchetypes::load(format!("{}/enemies.ron", root));
+    let weapons = config::Weapons::load(format!("{}/weapons.ron", root));
+    let controls = controls::Controls::read();
     let pipe = Pipeline::build().with_stage(
         Stage::with_backbuffer()
             .clear_target([0.1, 0.1, 0.1, 1.0], 1.0)
@@ -41,20 +53,24 @@ pub fn run() -> amethyst::Result<()> {
     let game_data = GameDataBuilder::default()
         .with_bundle(TransformBundle::new())?
         .with_bundle(
-            InputBundle::<String, String>::new()
-                .with_bindings_from_file(format!("{}/input.ron", root))?,
-        )?.with(
+            InputBundle::<String, String>::new().with_bindings(controls.keyboard.bindings()),
+        )?.with_thread_local(system::gamepad::Poll::default())
+        .with(
             amethyst::utils::ortho_camera::CameraOrthoSystem::default(),
             "OrthoCamera",
             &[],
         ).with_bundle(
-            RenderBundle::new(pipe, Some(display_config))
+            RenderBundle::new(pipe, Some(display_config.clone()))
                 .with_sprite_sheet_processor()
                 .with_sprite_visibility_sorting(&[]), // Let's us use the `Transparent` component
         )?;
 
-    let mut game = Application::build(root, Menu)?
+    let mut game = Application::build(root, Loading::default())?
+        .with_resource(display_config)
         .with_resource(gameoff_config)
+        .with_resource(enemy_archetypes)
+        .with_resource(weapons)
+        .with_resource(controls)
         .build(game_data)?;
     game.run();
     Ok(())
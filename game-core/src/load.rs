@@ -1,40 +1,33 @@
 use amethyst::{
-    assets::{AssetStorage, Loader},
+    assets::{AssetStorage, Loader, ProgressCounter},
     prelude::*,
     renderer::{
         MaterialTextureSet, PngFormat, SpriteSheet, SpriteSheetFormat, SpriteSheetHandle, Texture,
         TextureMetadata,
     },
 };
-use std::collections::HashMap;
 
-#[derive(Default)]
-pub struct LoadedTextures {
-    pub textures: HashMap<String, SpriteSheetHandle>,
-}
-
-pub fn sprite_sheet(world: &mut World, png_path: &str, ron_path: &str) -> SpriteSheetHandle {
-    let texture_id = super::load::texture(world, png_path);
+pub fn sprite_sheet(
+    world: &mut World,
+    png_path: &str,
+    ron_path: &str,
+    progress: &mut ProgressCounter,
+) -> SpriteSheetHandle {
+    let texture_id = super::load::texture(world, png_path, progress);
 
     let loader = world.read_resource::<Loader>();
     let sprite_sheet_store = world.read_resource::<AssetStorage<SpriteSheet>>();
-    let handle = loader.load(
+    loader.load(
         ron_path,
         SpriteSheetFormat,
         texture_id,
-        (),
+        progress,
         &sprite_sheet_store,
-    );
-
-    let mut my = world.write_resource::<LoadedTextures>();
-    let old_val = my.textures.insert(png_path.into(), handle.clone());
-    assert!(old_val.is_none());
-
-    handle
+    )
 }
 
 /// Loads texture into world and returns texture id.
-pub fn texture(world: &mut World, png_path: &str) -> u64 {
+pub fn texture(world: &mut World, png_path: &str, progress: &mut ProgressCounter) -> u64 {
     let texture_handle = {
         let loader = world.read_resource::<Loader>();
         let texture_storage = world.read_resource::<AssetStorage<Texture>>();
@@ -42,7 +35,7 @@ pub fn texture(world: &mut World, png_path: &str) -> u64 {
             png_path,
             PngFormat,
             TextureMetadata::srgb_scale(),
-            (),
+            progress,
             &texture_storage,
         )
     };
@@ -1,111 +1,392 @@
+use amethyst::core::cgmath::{Vector2, Vector3};
 use amethyst::renderer::Sprite;
 use amethyst::utils::application_root_dir;
 use amethyst::{
-    assets::{AssetStorage, Loader},
-    core::Transform,
+    assets::{AssetStorage, Loader, ProgressCounter},
+    ecs::Entity,
     prelude::*,
-    renderer::{SpriteRender, SpriteSheet},
+    renderer::{SpriteRender, SpriteSheet, SpriteSheetHandle},
 };
+use crate::assets::Assets;
+use crate::error::{Error, Result};
 use crate::load;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use std::sync::Arc;
 
-pub fn load_map_sprites(world: &mut World) {
-    let fname = format!("{}/resources/testmap.tmx", application_root_dir());
-    let file = Path::new(&fname);
-    let map = tiled::parse_file(&file).unwrap();
+pub const TILE_SIZE: f32 = 32.0;
 
-    let tileset = &map.tilesets[0];
-    let image = &tileset.images[0];
+/// Tiles along each side of a chunk.
+const CHUNK_TILES: u32 = 16;
 
-    let sprite_cords = |sprite_id| {
-        let width = image.width as u32 - 2 * tileset.margin + tileset.spacing;
-        let cols = width / (tileset.tile_width + tileset.spacing);
+// Tiled stores flips in the top bits of a gid
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+const GID_MASK: u32 = 0x1FFF_FFFF;
 
-        let col = sprite_id % cols;
-        let row = sprite_id / cols;
+/// Parsed Tiled maps by file name, so changing levels doesn't go back to disk.
+#[derive(Default)]
+pub struct Maps {
+    maps: HashMap<String, Arc<tiled::Map>>,
+}
+
+impl Maps {
+    /// Returns the parsed map, parsing it first if this is the first request.
+    pub fn load(&mut self, map_file: &str) -> Result<Arc<tiled::Map>> {
+        if let Some(map) = self.maps.get(map_file) {
+            return Ok(map.clone());
+        }
+
+        let fname = format!("{}/resources/{}", application_root_dir(), map_file);
+        let map = match tiled::parse_file(Path::new(&fname)) {
+            Ok(map) => Arc::new(map),
+            Err(cause) => return Err(Error::Map { path: fname, cause }),
+        };
+
+        self.maps.insert(map_file.into(), map.clone());
+        Ok(map)
+    }
+}
 
-        let left = tileset.margin + tileset.tile_width * col + tileset.spacing * col;
-        let top = tileset.margin + tileset.tile_height * row + tileset.spacing * row;
+/// Sorts the map's tiles into `TileChunks` for `system::tiles::Chunks` to
+/// spawn, and adds the map's spawn points and passable tiles to the world.
+pub fn load_map_sprites(world: &mut World, map_file: &str) -> Result<()> {
+    let map = world.write_resource::<Maps>().load(map_file)?;
 
-        (left, top)
+    // (first_gid, sheet), sorted so a gid belongs to the last tileset whose
+    // first_gid it's not below. Looked up before anything is added to the
+    // world so a broken map doesn't leave half a level behind.
+    let mut sheets = {
+        let assets = world.read_resource::<Assets>();
+        map.tilesets
+            .iter()
+            .map(|tileset| Ok((tileset.first_gid, assets.tileset(&tileset.name)?)))
+            .collect::<Result<Vec<(u32, SpriteSheetHandle)>>>()?
     };
+    sheets.sort_by_key(|(first_gid, _)| *first_gid);
 
-    let texture_id = load::texture(world, &image.source);
+    let solid = solid_gids(&map.tilesets);
 
-    let handle = {
-        let loader = world.read_resource::<Loader>();
-        let sprite_sheet_store = world.read_resource::<AssetStorage<SpriteSheet>>();
+    let tile_width = map.tile_width as f32;
+    let tile_height = map.tile_height as f32;
+    let mut passable = vec![vec![true; map.width as usize]; map.height as usize];
+    let mut chunks = TileChunks {
+        chunk_width: CHUNK_TILES as f32 * tile_width,
+        chunk_height: CHUNK_TILES as f32 * tile_height,
+        columns: (map.width + CHUNK_TILES - 1) / CHUNK_TILES,
+        rows: (map.height + CHUNK_TILES - 1) / CHUNK_TILES,
+        ..TileChunks::default()
+    };
 
-        let mut sprites: Vec<Sprite> = Vec::with_capacity(tileset.tiles.len());
+    for (depth, layer) in map.layers.iter().enumerate() {
+        // first layer at the back, each following one drawn over it
+        let z = -1.0 + depth as f32 * 0.1;
 
-        for tile in &tileset.tiles {
-            let (left, top) = sprite_cords(tile.id);
-
-            let sprite = Sprite::from_pixel_values(
-                image.width as u32,
-                image.height as u32,
-                tileset.tile_width,
-                tileset.tile_height,
-                left,
-                top,
-                [0, 0],
-            );
+        for (y, row) in layer.tiles.iter().rev().enumerate() {
+            for (x, raw_gid) in row.iter().enumerate() {
+                let gid = raw_gid & GID_MASK;
+                if gid == 0 {
+                    continue;
+                }
 
-            sprites.push(sprite);
+                if solid.contains(&gid) {
+                    passable[y][x] = false;
+                }
+
+                // hidden layers still block, they just aren't drawn
+                if !layer.visible {
+                    continue;
+                }
+
+                let (first_gid, sheet) = match sheets.iter().rev().find(|(f, _)| *f <= gid) {
+                    Some(sheet) => sheet,
+                    None => continue,
+                };
+
+                let tile = TileSprite {
+                    translation: Vector3 {
+                        x: (x as f32 + 0.5) * tile_width,
+                        y: (y as f32 + 0.5) * tile_height,
+                        z,
+                    },
+                    sprite: SpriteRender {
+                        sprite_sheet: sheet.clone(),
+                        sprite_number: (gid - first_gid) as usize,
+                        flip_horizontal: raw_gid & FLIPPED_HORIZONTALLY != 0,
+                        flip_vertical: raw_gid & FLIPPED_VERTICALLY != 0,
+                    },
+                };
+
+                let key = (x as u32 / CHUNK_TILES, y as u32 / CHUNK_TILES);
+                chunks
+                    .chunks
+                    .entry(key)
+                    .or_insert_with(Chunk::default)
+                    .tiles
+                    .push(tile);
+            }
         }
+    }
 
-        loader.load_from_data(
-            SpriteSheet {
-                texture_id,
-                sprites,
-            },
-            (),
-            &sprite_sheet_store,
-        )
-    };
+    let map_height = map.height as f32 * tile_height;
+    world.add_resource(chunks);
+    world.add_resource(MapSpawns::from_objects(&map.object_groups, map_height));
+    world.add_resource(PassableTiles {
+        tile_matrix: passable,
+    });
 
-    let mut left = (tileset.tile_width / 2) as f32;
-    let mut top = (tileset.tile_height / 2) as f32;
+    Ok(())
+}
 
-    let layer = &map.layers[0];
-    let mut passable: Vec<Vec<bool>> = Vec::with_capacity(layer.tiles.len());
+/// A tile to draw once its chunk comes into view.
+pub struct TileSprite {
+    pub translation: Vector3<f32>,
+    pub sprite: SpriteRender,
+}
 
-    for row in layer.tiles.iter().rev() {
-        let mut passable_row: Vec<bool> = Vec::with_capacity(row.len());
+/// A square of `CHUNK_TILES` tiles, from every layer.
+#[derive(Default)]
+pub struct Chunk {
+    pub tiles: Vec<TileSprite>,
+    /// One per tile while the chunk is spawned, empty otherwise.
+    pub entities: Vec<Entity>,
+}
 
-        for tile_id in row {
-            passable_row.push(*tile_id != 30);
+/// The map's tiles, keyed by chunk column and row counted from the bottom
+/// left. Only the chunks near the camera exist as entities, so the renderer
+/// doesn't walk the whole map every frame.
+#[derive(Default)]
+pub struct TileChunks {
+    pub chunks: HashMap<(u32, u32), Chunk>,
+    /// Chunks that currently have entities.
+    pub spawned: HashSet<(u32, u32)>,
+    /// Size of a chunk in pixels.
+    pub chunk_width: f32,
+    pub chunk_height: f32,
+    /// Chunks across and up the map.
+    pub columns: u32,
+    pub rows: u32,
+}
 
-            if *tile_id != 30 && *tile_id != 0 {
-                let mut transform = Transform::default();
-                transform.translation.z = -1.0;
-                transform.translation.x = left;
-                transform.translation.y = top;
+impl TileChunks {
+    /// Chunks with tiles in them that overlap the rectangle from `min` to
+    /// `max`.
+    pub fn overlapping(&self, min: Vector2<f32>, max: Vector2<f32>) -> Vec<(u32, u32)> {
+        if self.columns == 0 || self.rows == 0 || max.x < 0.0 || max.y < 0.0 {
+            return vec![];
+        }
 
-                let sprite = SpriteRender {
-                    sprite_sheet: handle.clone(),
-                    sprite_number: *tile_id as usize - 1,
-                    flip_horizontal: false,
-                    flip_vertical: false,
-                };
+        let first_x = (min.x / self.chunk_width).floor().max(0.0) as u32;
+        let first_y = (min.y / self.chunk_height).floor().max(0.0) as u32;
+        let last_x = ((max.x / self.chunk_width).floor() as u32).min(self.columns - 1);
+        let last_y = ((max.y / self.chunk_height).floor() as u32).min(self.rows - 1);
 
-                world.create_entity().with(transform).with(sprite).build();
+        let mut keys = vec![];
+        for y in first_y..=last_y {
+            for x in first_x..=last_x {
+                if self.chunks.contains_key(&(x, y)) {
+                    keys.push((x, y));
+                }
             }
+        }
+        keys
+    }
+}
 
-            left += tileset.tile_width as f32;
+/// Starts loading a sprite sheet with one sprite per tile of `tileset`, so
+/// sprite numbers match tile ids.
+pub fn tileset_sprite_sheet(
+    world: &mut World,
+    tileset: &tilesheet::Tileset,
+    progress: &mut ProgressCounter,
+) -> SpriteSheetHandle {
+    let texture_id = load::texture(world, &tileset.image, progress);
+
+    let sprites = tileset
+        .tiles()
+        .into_iter()
+        .map(|tile| {
+            Sprite::from_pixel_values(
+                tileset.image_width,
+                tileset.image_height,
+                tile.width,
+                tile.height,
+                tile.x,
+                tile.y,
+                [0, 0],
+            )
+        })
+        .collect();
+
+    let loader = world.read_resource::<Loader>();
+    let sprite_sheet_store = world.read_resource::<AssetStorage<SpriteSheet>>();
+    loader.load_from_data(
+        SpriteSheet {
+            texture_id,
+            sprites,
+        },
+        progress,
+        &sprite_sheet_store,
+    )
+}
+
+/// Gids of every tile with the custom property `solid = true`.
+fn solid_gids(tilesets: &[tiled::Tileset]) -> HashSet<u32> {
+    let mut solid = HashSet::new();
+    for tileset in tilesets {
+        for tile in &tileset.tiles {
+            if let Some(tiled::PropertyValue::BoolValue(true)) = tile.properties.get("solid") {
+                solid.insert(tileset.first_gid + tile.id);
+            }
         }
+    }
+    solid
+}
 
-        passable.push(passable_row);
+pub struct Region {
+    pub center: Vector2<f32>,
+    pub half_extents: Vector2<f32>,
+}
 
-        left = (tileset.tile_width / 2) as f32;
-        top += tileset.tile_height as f32;
+impl Region {
+    pub fn contains(&self, pos: Vector2<f32>) -> bool {
+        (pos.x - self.center.x).abs() <= self.half_extents.x
+            && (pos.y - self.center.y).abs() <= self.half_extents.y
     }
+}
 
-    world.add_resource(PassableTiles {
-        tile_matrix: passable,
-    });
+pub struct SpawnArea {
+    pub region: Region,
+    pub max: u32,
+}
+
+pub struct Exit {
+    pub region: Region,
+    pub level: String,
+    pub entry: Option<String>,
+}
+
+/// Things placed on the map's object layers, by their Tiled `type`:
+/// `player_start` (keyed by object name), `enemy_spawner` (with an optional
+/// int property `max`), `ally`, `exit` (with string properties `level` and
+/// optionally `entry`) and `goal`, which wins the game when the player reaches
+/// it.
+#[derive(Default)]
+pub struct MapSpawns {
+    pub player_starts: HashMap<String, Vector2<f32>>,
+    pub enemy_spawners: Vec<SpawnArea>,
+    pub ally_pickups: Vec<Vector2<f32>>,
+    pub exits: Vec<Exit>,
+    pub goals: Vec<Region>,
+}
+
+impl MapSpawns {
+    fn from_objects(groups: &[tiled::ObjectGroup], map_height: f32) -> Self {
+        let mut spawns = MapSpawns::default();
+
+        for object in groups.iter().flat_map(|group| group.objects.iter()) {
+            // Tiled measures y down from the top of the map, and from the
+            // bottom edge for tile objects but the top edge for everything else
+            let top = if object.gid != 0 {
+                object.y - object.height
+            } else {
+                object.y
+            };
+            let center = Vector2 {
+                x: object.x + object.width / 2.0,
+                y: map_height - (top + object.height / 2.0),
+            };
+
+            let region = Region {
+                center,
+                half_extents: Vector2 {
+                    x: object.width / 2.0,
+                    y: object.height / 2.0,
+                },
+            };
+
+            match object.obj_type.as_str() {
+                "player_start" => {
+                    spawns.player_starts.insert(object.name.clone(), center);
+                }
+                "enemy_spawner" => {
+                    let max = match object.properties.get("max") {
+                        Some(tiled::PropertyValue::IntValue(max)) => (*max).max(0) as u32,
+                        _ => 3,
+                    };
+                    spawns.enemy_spawners.push(SpawnArea { region, max });
+                }
+                "ally" => spawns.ally_pickups.push(center),
+                "exit" => {
+                    let string_property = |name| match object.properties.get(name) {
+                        Some(tiled::PropertyValue::StringValue(value)) => Some(value.clone()),
+                        _ => None,
+                    };
+
+                    match string_property("level") {
+                        Some(level) => spawns.exits.push(Exit {
+                            region,
+                            level,
+                            entry: string_property("entry"),
+                        }),
+                        None => warn!("exit {:?} has no level property", object.name),
+                    }
+                }
+                "goal" => spawns.goals.push(region),
+                other => warn!(
+                    "ignoring map object {:?} of unknown type {:?}",
+                    object.name, other
+                ),
+            }
+        }
+
+        spawns
+    }
 }
 
 pub struct PassableTiles {
     pub tile_matrix: Vec<Vec<bool>>,
 }
+
+impl PassableTiles {
+    pub fn is_passable(&self, x: usize, y: usize) -> bool {
+        *self
+            .tile_matrix
+            .get(y)
+            .and_then(|row| row.get(x))
+            .unwrap_or(&false)
+    }
+
+    /// Size of the map in pixels.
+    pub fn size(&self) -> Vector2<f32> {
+        let columns = self.tile_matrix.first().map_or(0, |row| row.len());
+        Vector2 {
+            x: columns as f32 * TILE_SIZE,
+            y: self.tile_matrix.len() as f32 * TILE_SIZE,
+        }
+    }
+
+    pub fn tile_at(&self, pos: Vector2<f32>) -> Option<(usize, usize)> {
+        if pos.x < 0.0 || pos.y < 0.0 {
+            return None;
+        }
+
+        Some(((pos.x / TILE_SIZE) as usize, (pos.y / TILE_SIZE) as usize))
+    }
+
+    /// Whether every tile touched by the box centred on `center` is passable.
+    pub fn is_area_passable(&self, center: Vector2<f32>, half_extents: Vector2<f32>) -> bool {
+        // shrink a hair so a box exactly one tile wide fits a one tile gap
+        let half = half_extents - Vector2 { x: 0.01, y: 0.01 };
+        let min_x = ((center.x - half.x) / TILE_SIZE).floor() as i64;
+        let max_x = ((center.x + half.x) / TILE_SIZE).floor() as i64;
+        let min_y = ((center.y - half.y) / TILE_SIZE).floor() as i64;
+        let max_y = ((center.y + half.y) / TILE_SIZE).floor() as i64;
+
+        if min_x < 0 || min_y < 0 {
+            return false;
+        }
+
+        (min_y..=max_y).all(|y| (min_x..=max_x).all(|x| self.is_passable(x as usize, y as usize)))
+    }
+}
@@ -0,0 +1,281 @@
+use amethyst::core::cgmath::Vector2;
+use crate::component::Path;
+use crate::map::{PassableTiles, TILE_SIZE};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+/// (column, row) into `PassableTiles::tile_matrix`.
+pub type Tile = (usize, usize);
+
+/// Upper bound on expanded nodes so an unreachable goal can't stall a frame.
+const MAX_EXPANDED: usize = 4096;
+
+const STRAIGHT: u32 = 10;
+const DIAGONAL: u32 = 14;
+
+#[derive(PartialEq, Eq)]
+struct Node {
+    estimate: u32,
+    cost: u32,
+    tile: Tile,
+}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is a max-heap, flip it so the cheapest estimate pops first
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then_with(|| self.cost.cmp(&other.cost))
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn heuristic(a: Tile, b: Tile) -> u32 {
+    let dx = (a.0 as i64 - b.0 as i64).abs() as u32;
+    let dy = (a.1 as i64 - b.1 as i64).abs() as u32;
+    STRAIGHT * dx.max(dy) + (DIAGONAL - STRAIGHT) * dx.min(dy)
+}
+
+fn neighbours(passable: &PassableTiles, (x, y): Tile) -> Vec<(Tile, u32)> {
+    let mut out = Vec::with_capacity(8);
+    for dy in -1i64..=1 {
+        for dx in -1i64..=1 {
+            if dx == 0 && dy == 0 {
+                continue;
+            }
+
+            let nx = x as i64 + dx;
+            let ny = y as i64 + dy;
+            if nx < 0 || ny < 0 || !passable.is_passable(nx as usize, ny as usize) {
+                continue;
+            }
+
+            if dx != 0 && dy != 0 {
+                // don't cut corners around walls
+                if !passable.is_passable(nx as usize, y) || !passable.is_passable(x, ny as usize) {
+                    continue;
+                }
+                out.push(((nx as usize, ny as usize), DIAGONAL));
+            } else {
+                out.push(((nx as usize, ny as usize), STRAIGHT));
+            }
+        }
+    }
+    out
+}
+
+/// A* over the passable tiles. The returned path excludes `start` and ends at
+/// `goal`.
+pub fn find_path(passable: &PassableTiles, start: Tile, goal: Tile) -> Option<Vec<Tile>> {
+    if !passable.is_passable(goal.0, goal.1) {
+        return None;
+    }
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<Tile, Tile> = HashMap::new();
+    let mut best: HashMap<Tile, u32> = HashMap::new();
+
+    best.insert(start, 0);
+    open.push(Node {
+        estimate: heuristic(start, goal),
+        cost: 0,
+        tile: start,
+    });
+
+    let mut expanded = 0;
+    while let Some(Node { cost, tile, .. }) = open.pop() {
+        if tile == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while let Some(&prev) = came_from.get(&current) {
+                if prev == start {
+                    break;
+                }
+                path.push(prev);
+                current = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        if cost > best[&tile] {
+            continue; // stale entry
+        }
+
+        expanded += 1;
+        if expanded > MAX_EXPANDED {
+            return None;
+        }
+
+        for (next, step) in neighbours(passable, tile) {
+            let next_cost = cost + step;
+            if best.get(&next).map_or(true, |&c| next_cost < c) {
+                best.insert(next, next_cost);
+                came_from.insert(next, tile);
+                open.push(Node {
+                    estimate: next_cost + heuristic(next, goal),
+                    cost: next_cost,
+                    tile: next,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Returns the point something at `from` should head towards to get to `to`,
+/// re-planning `path` whenever `to` has moved onto a different tile. Falls
+/// back to a straight line when there's no map or no route.
+pub fn next_waypoint(
+    path: &mut Path,
+    passable: Option<&PassableTiles>,
+    from: Vector2<f32>,
+    to: Vector2<f32>,
+) -> Vector2<f32> {
+    let passable = match passable {
+        Some(passable) => passable,
+        None => return to,
+    };
+
+    let (start, goal) = match (passable.tile_at(from), passable.tile_at(to)) {
+        (Some(start), Some(goal)) => (start, goal),
+        _ => return to,
+    };
+
+    if start == goal {
+        path.clear();
+        return to;
+    }
+
+    while path.tiles.last() == Some(&start) {
+        path.tiles.pop();
+    }
+
+    // got pushed off the route, e.g. by other allies
+    let off_course = path
+        .tiles
+        .last()
+        .map_or(false, |&next| heuristic(start, next) > DIAGONAL);
+
+    if path.goal != Some(goal) || off_course {
+        path.goal = Some(goal);
+        path.tiles = find_path(passable, start, goal).unwrap_or_default();
+        path.tiles.reverse(); // next tile at the back so it can be popped
+    }
+
+    match path.tiles.last() {
+        // the final tile is where the target is, so aim for the target itself
+        Some(&tile) if tile != goal => tile_center(tile),
+        _ => to,
+    }
+}
+
+pub fn tile_center((x, y): Tile) -> Vector2<f32> {
+    Vector2 {
+        x: (x as f32 + 0.5) * TILE_SIZE,
+        y: (y as f32 + 0.5) * TILE_SIZE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `#` is a wall, anything else is floor. Each string is a row, `y` = 0
+    /// first.
+    fn map(rows: &[&str]) -> PassableTiles {
+        PassableTiles {
+            tile_matrix: rows
+                .iter()
+                .map(|row| row.chars().map(|c| c != '#').collect())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn walks_straight_across_open_ground() {
+        let passable = map(&["....."]);
+
+        assert_eq!(
+            find_path(&passable, (0, 0), (4, 0)),
+            Some(vec![(1, 0), (2, 0), (3, 0), (4, 0)])
+        );
+    }
+
+    #[test]
+    fn goes_around_walls_without_cutting_corners() {
+        let passable = map(&[".....", "..#..", "..#.."]);
+
+        assert_eq!(
+            find_path(&passable, (0, 1), (4, 1)),
+            Some(vec![(1, 0), (2, 0), (3, 0), (4, 1)])
+        );
+    }
+
+    #[test]
+    fn gives_up_when_the_goal_is_cut_off() {
+        let passable = map(&["..#..", "..#..", "..#.."]);
+
+        assert_eq!(find_path(&passable, (0, 0), (4, 0)), None);
+        assert_eq!(find_path(&passable, (0, 0), (2, 0)), None);
+    }
+
+    #[test]
+    fn stops_after_max_expanded_tiles() {
+        // the only way through is at the far end of a wall between the two,
+        // so every tile in the bottom row gets expanded first
+        let detour = |width| {
+            let rows = [
+                ".".repeat(width),
+                "#".repeat(width - 1) + ".",
+                ".".repeat(width),
+            ];
+            map(&[&rows[0], &rows[1], &rows[2]])
+        };
+
+        assert!(find_path(&detour(MAX_EXPANDED / 4), (0, 0), (0, 2)).is_some());
+        assert_eq!(find_path(&detour(MAX_EXPANDED), (0, 0), (0, 2)), None);
+    }
+
+    #[test]
+    fn next_waypoint_follows_the_planned_path() {
+        let passable = map(&[".....", "..#..", "..#.."]);
+        let mut path = Path::default();
+        let to = tile_center((4, 1));
+
+        let waypoint = next_waypoint(&mut path, Some(&passable), tile_center((0, 1)), to);
+
+        assert_eq!(waypoint, tile_center((1, 0)));
+        assert_eq!(path.goal, Some((4, 1)));
+        assert_eq!(path.tiles, vec![(4, 1), (3, 0), (2, 0), (1, 0)]);
+
+        // reaching a tile moves on to the next one without planning again
+        let waypoint = next_waypoint(&mut path, Some(&passable), tile_center((1, 0)), to);
+        assert_eq!(waypoint, tile_center((2, 0)));
+        assert_eq!(path.tiles, vec![(4, 1), (3, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn next_waypoint_heads_straight_for_the_target_when_it_can() {
+        let passable = map(&["....."]);
+        let mut path = Path::default();
+        let from = Vector2 { x: 8.0, y: 8.0 };
+        let to = Vector2 { x: 24.0, y: 16.0 };
+
+        // no map to plan on
+        assert_eq!(next_waypoint(&mut path, None, from, to), to);
+        // already on the target's tile
+        assert_eq!(next_waypoint(&mut path, Some(&passable), from, to), to);
+        assert_eq!(path.goal, None);
+        // on the last tile of the path
+        let to = tile_center((1, 0));
+        assert_eq!(next_waypoint(&mut path, Some(&passable), from, to), to);
+    }
+}
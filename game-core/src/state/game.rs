@@ -1,14 +1,195 @@
-use amethyst::{ecs::prelude::*, prelude::*};
+use amethyst::{
+    core::Transform,
+    ecs::prelude::*,
+    input::is_key_down,
+    prelude::*,
+    renderer::{Camera, VirtualKeyCode},
+    utils::ortho_camera::{CameraNormalizeMode, CameraOrtho},
+};
+use config::{GameoffConfig, Weapons};
+use crate::assets::{AnimationId, Assets, SheetId};
+use crate::error::{Error, Result};
+use crate::level::LevelChange;
+use crate::state::{GameOver, LoadFailed, Paused, Victory};
+use crate::system::health::Score;
+use crate::system::projectile::ProjectilePool;
+use crate::system::*;
+use component::{Player, Team, Weapon};
 
+#[derive(Default)]
 pub struct Game<'a, 'b> {
-    pub dispatcher: Dispatcher<'a, 'b>,
+    pub dispatcher: Option<Dispatcher<'a, 'b>>,
+    // reported from `update`, since `on_start` can't transition
+    error: Option<Error>,
 }
 
 impl<'a, 'b> State<GameData<'a, 'b>, StateEvent> for Game<'a, 'b> {
+    fn on_start(&mut self, data: StateData<GameData<'a, 'b>>) {
+        let world = data.world;
+
+        world.add_resource(Score::default());
+
+        world.add_resource(LevelChange::default());
+
+        world.add_resource(ProjectilePool::default());
+
+        if let Err(error) = start_first_level(world) {
+            self.error = Some(error);
+            return;
+        }
+
+        let mut dispatcher = DispatcherBuilder::new()
+            .with(player::Movement, "player-movement", &[])
+            .with(enemy::Think, "enemy-think", &[])
+            .with(enemy::Movement, "enemy-movement", &["enemy-think"])
+            .with(camera::Movement, "camera-movement", &[])
+            .with(tiles::Chunks, "tile-chunks", &["camera-movement"])
+            .with(enemy::Spawner, "enemy-spawner", &[])
+            .with(ally::Movement, "ally-movement", &[])
+            .with(ally::Grouper, "ally-grouper", &[])
+            .with(ally::Spawner, "ally-spawner", &[])
+            .with(player::Attack, "player-attack", &[])
+            .with(enemy::Attack, "enemy-attack", &["enemy-think"])
+            .with(
+                weapon::Fire,
+                "weapon-fire",
+                &["player-attack", "enemy-attack"],
+            ).with(
+                motion::Movement,
+                "projectile-movement",
+                &["player-movement"],
+            ).with(
+                collision::Detection,
+                "collision-detection",
+                &["projectile-movement"],
+            ).with(
+                projectile::Despawn::default(),
+                "projectile-despawn",
+                &["collision-detection"],
+            ).with(health::Update, "health-update", &["collision-detection"])
+            .with(health::Death::default(), "death", &["health-update"])
+            .with(
+                animation::Select::default(),
+                "animation-select",
+                &["player-movement", "weapon-fire", "health-update"],
+            ).with(animation::Frame, "frame-animation", &["animation-select"])
+            .with(level::Exits::default(), "level-exits", &["player-movement"])
+            .build();
+        dispatcher.setup(&mut world.res);
+        self.dispatcher = Some(dispatcher);
+    }
+
+    fn on_stop(&mut self, data: StateData<GameData<'a, 'b>>) {
+        // everything in the world belongs to this run, the next one starts
+        // from a fresh map
+        self.dispatcher = None;
+        data.world.delete_all();
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<GameData<'a, 'b>>,
+        event: StateEvent,
+    ) -> Trans<GameData<'a, 'b>, StateEvent> {
+        if let StateEvent::Window(event) = &event {
+            if is_key_down(event, VirtualKeyCode::Escape) {
+                return Trans::Push(Box::new(Paused));
+            }
+
+            if is_key_down(event, VirtualKeyCode::F3) {
+                let mut pool = data.world.write_resource::<ProjectilePool>();
+                pool.show_count = !pool.show_count;
+            }
+        }
+
+        Trans::None
+    }
+
     fn update(&mut self, data: StateData<GameData<'a, 'b>>) -> Trans<GameData<'a, 'b>, StateEvent> {
+        if let Some(error) = self.error.take() {
+            return Trans::Switch(Box::new(LoadFailed { error }));
+        }
+
         data.data.update(&data.world);
-        self.dispatcher.dispatch(&data.world.res);
+        if let Some(dispatcher) = self.dispatcher.as_mut() {
+            dispatcher.dispatch(&data.world.res);
+        }
+
+        let change = data.world.write_resource::<LevelChange>().target.take();
+        if let Some(target) = change {
+            if let Err(error) = crate::level::change(data.world, &target) {
+                return Trans::Switch(Box::new(LoadFailed { error }));
+            }
+        }
+
+        let score = data.world.read_resource::<Score>();
+        if score.player_dead {
+            return Trans::Switch(Box::new(GameOver {
+                kills: score.kills,
+                allies_lost: score.allies_lost,
+            }));
+        }
+
+        if score.player_won {
+            return Trans::Switch(Box::new(Victory {
+                kills: score.kills,
+                allies_lost: score.allies_lost,
+            }));
+        }
 
         Trans::None
     }
 }
+
+fn start_first_level(world: &mut World) -> Result<()> {
+    let first_level = world
+        .read_resource::<GameoffConfig>()
+        .levels
+        .first()
+        .cloned()
+        .unwrap_or_default();
+    let start = crate::level::load(world, &first_level, None)?;
+
+    let (player_sprite_sheet_handle, animation) = {
+        let assets = world.read_resource::<Assets>();
+        (
+            assets.sheet(&SheetId::Player)?,
+            assets.animation(&AnimationId::Player)?,
+        )
+    };
+    let weapon = {
+        let config = world.read_resource::<GameoffConfig>();
+        let def = world
+            .read_resource::<Weapons>()
+            .get(&config.player_weapon)
+            .cloned()
+            .ok_or_else(|| Error::UnknownWeapon(config.player_weapon.clone()))?;
+        Weapon::new(def, Team::Player)
+    };
+    let parent = Player::new(world, &player_sprite_sheet_handle, animation, weapon, start);
+    init_camera(world, parent);
+
+    Ok(())
+}
+
+pub fn init_camera(world: &mut World, parent: Entity) {
+    let mut transform = {
+        let transforms = world.read_storage::<Transform>();
+        transforms.get(parent).unwrap().clone()
+    };
+
+    world.register::<CameraOrtho>();
+
+    transform.translation.z = 2.0;
+    transform.translation.x -= 256.0;
+    transform.translation.y -= 256.0;
+    transform.scale.x = 512.0;
+    transform.scale.y = 512.0;
+
+    world
+        .create_entity()
+        .with(CameraOrtho::normalized(CameraNormalizeMode::Contain))
+        .with(Camera::standard_2d())
+        .with(transform)
+        .build();
+}
@@ -0,0 +1,35 @@
+use amethyst::{input::is_key_down, prelude::*, renderer::VirtualKeyCode};
+use crate::state::Game;
+
+pub struct GameOver {
+    pub kills: u32,
+    pub allies_lost: u32,
+}
+
+impl<'a, 'b> SimpleState<'a, 'b> for GameOver {
+    fn on_start(&mut self, _data: StateData<GameData>) {
+        info!(
+            "Game over! {} enemies popped, {} allies lost",
+            self.kills, self.allies_lost
+        );
+        info!("Press Enter to play again, Escape to return to the menu");
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<GameData>,
+        event: StateEvent,
+    ) -> Trans<GameData<'a, 'b>, StateEvent> {
+        if let StateEvent::Window(event) = &event {
+            if is_key_down(event, VirtualKeyCode::Return) {
+                return Trans::Switch(Box::new(Game::default()));
+            }
+
+            if is_key_down(event, VirtualKeyCode::Escape) {
+                return Trans::Pop;
+            }
+        }
+
+        Trans::None
+    }
+}
@@ -0,0 +1,40 @@
+use amethyst::{
+    input::{is_close_requested, is_key_down},
+    prelude::*,
+    renderer::VirtualKeyCode,
+};
+use crate::error::Error;
+
+/// Shown instead of crashing when assets or a level fail to load. Leaving it
+/// pops back to whatever state is underneath, which quits the game if
+/// nothing is.
+pub struct LoadFailed {
+    pub error: Error,
+}
+
+impl<'a, 'b> SimpleState<'a, 'b> for LoadFailed {
+    fn on_start(&mut self, _data: StateData<GameData>) {
+        error!("{}", self.error);
+        info!("Press Enter or Escape to continue");
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<GameData>,
+        event: StateEvent,
+    ) -> Trans<GameData<'a, 'b>, StateEvent> {
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(event) {
+                return Trans::Quit;
+            }
+
+            if is_key_down(event, VirtualKeyCode::Return)
+                || is_key_down(event, VirtualKeyCode::Escape)
+            {
+                return Trans::Pop;
+            }
+        }
+
+        Trans::None
+    }
+}
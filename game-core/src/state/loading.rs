@@ -0,0 +1,105 @@
+use amethyst::{
+    assets::ProgressCounter,
+    input::{is_close_requested, is_key_down},
+    prelude::*,
+    renderer::VirtualKeyCode,
+};
+use component::{Ally, Animation, Collider, Health, Player};
+use config::GameoffConfig;
+use crate::assets::{Assets, Manifest};
+use crate::error::{Error, Result};
+use crate::map::Maps;
+use crate::state::{LoadFailed, Menu};
+
+/// First state of the game. Loads everything in the asset manifest and every
+/// level's map, and only moves on to the menu once all of it has arrived.
+#[derive(Default)]
+pub struct Loading {
+    progress: ProgressCounter,
+    error: Option<Error>,
+    finished: usize,
+}
+
+impl SimpleState<'static, 'static> for Loading {
+    fn on_start(&mut self, data: StateData<GameData>) {
+        let world = data.world;
+
+        world.register::<Player>();
+        world.register::<Ally>();
+        world.register::<Animation>();
+        world.register::<Collider>();
+        world.register::<Health>();
+
+        if let Err(error) = load_all(world, &mut self.progress) {
+            self.error = Some(error);
+            return;
+        }
+
+        info!("Loading {} assets", self.progress.num_assets());
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<GameData>,
+        event: StateEvent,
+    ) -> Trans<GameData<'static, 'static>, StateEvent> {
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(event) || is_key_down(event, VirtualKeyCode::Escape) {
+                return Trans::Quit;
+            }
+        }
+
+        Trans::None
+    }
+
+    fn update(&mut self, _data: &mut StateData<GameData>) -> SimpleTrans<'static, 'static> {
+        if let Some(error) = self.error.take() {
+            return Trans::Switch(Box::new(LoadFailed { error }));
+        }
+
+        let finished = self.progress.num_finished() + self.progress.num_failed();
+        if finished != self.finished {
+            self.finished = finished;
+            info!("Loaded {}/{} assets", finished, self.progress.num_assets());
+        }
+
+        if !self.progress.is_complete() {
+            return Trans::None;
+        }
+
+        let errors = self.progress.errors();
+        if !errors.is_empty() {
+            for failed in &errors {
+                error!("failed to load {}: {}", failed.asset_name, failed.error);
+            }
+
+            return Trans::Switch(Box::new(LoadFailed {
+                error: Error::FailedAssets(errors.len()),
+            }));
+        }
+
+        Trans::Switch(Box::new(Menu))
+    }
+}
+
+fn load_all(world: &mut World, progress: &mut ProgressCounter) -> Result<()> {
+    let assets = Assets::load(world, Manifest::read()?, progress)?;
+    world.add_resource(assets);
+
+    // parse every map up front so a broken level is reported now rather than
+    // when the player walks into it
+    let map_files: Vec<String> = world
+        .read_resource::<GameoffConfig>()
+        .levels
+        .iter()
+        .map(|level| level.map.clone())
+        .collect();
+
+    let mut maps = Maps::default();
+    for map_file in map_files {
+        maps.load(&map_file)?;
+    }
+    world.add_resource(maps);
+
+    Ok(())
+}
@@ -1,85 +1,37 @@
 use amethyst::{
-    core::Transform,
-    ecs::prelude::*,
-    ecs::Entity,
+    input::{is_close_requested, is_key_down},
     prelude::*,
-    renderer::Camera,
-    utils::ortho_camera::{CameraNormalizeMode, CameraOrtho},
+    renderer::VirtualKeyCode,
 };
-use component::{Animation, Player};
-use crate::load;
 use crate::state::Game;
-use crate::system::*;
-use std::time::Duration;
 
+/// Title screen. Waits for the player to start a run.
 pub struct Menu;
 
 impl SimpleState<'static, 'static> for Menu {
-    fn on_start(&mut self, data: StateData<GameData>) {
-        let world = data.world;
-
-        world.register::<Player>();
-        world.register::<Animation>();
-
-        world.add_resource(load::LoadedTextures::default());
-
-        let player_sprite_sheet_handle = load::sprite_sheet(world, "FRONT.png", "FRONT.ron");
-        let _penguin_sprite_sheet_handle =
-            load::sprite_sheet(world, "penguinFront.png", "penguinFront.ron");
-        let _ = load::sprite_sheet(world, "bubble.png", "bubble.ron");
+    fn on_start(&mut self, _data: StateData<GameData>) {
+        info!("Press Enter to start, Escape to quit");
+    }
 
-        crate::map::load_map_sprites(world);
-        let parent = Player::new(world, &player_sprite_sheet_handle);
-        init_camera(world, parent);
+    fn on_resume(&mut self, _data: StateData<GameData>) {
+        info!("Press Enter to start, Escape to quit");
     }
 
-    fn update(
+    fn handle_event(
         &mut self,
-        data: &mut StateData<GameData>,
+        _data: StateData<GameData>,
+        event: StateEvent,
     ) -> Trans<GameData<'static, 'static>, StateEvent> {
-        let world = &mut data.world;
-        let mut dispatcher: Dispatcher = DispatcherBuilder::new()
-            .with(player::Movement, "player-movement", &[])
-            .with(
-                enemy::Movement {
-                    random_movement_time: Duration::new(0, 0),
-                    random_idle_time: Duration::new(1, 0),
-                },
-                "enemy-movement",
-                &[],
-            ).with(camera::Movement, "camera-movement", &[])
-            .with(enemy::Spawner, "enemy-spawner", &[])
-            .with(ally::Movement, "ally-movement", &[])
-            .with(ally::Grouper, "ally-grouper", &[])
-            .with(ally::Spawner, "ally-spawner", &[])
-            .with(player::Attack, "player-attack", &[])
-            .with(enemy::Attack, "enemy-attack", &[])
-            .with(animation::Frame, "frame-animation", &[])
-            .with(motion::Movement, "projectile-movement", &[])
-            .build();
-        dispatcher.setup(&mut world.res);
-        Trans::Push(Box::new(Game { dispatcher }))
-    }
-}
-
-pub fn init_camera(world: &mut World, parent: Entity) {
-    let mut transform = {
-        let transforms = world.read_storage::<Transform>();
-        transforms.get(parent).unwrap().clone()
-    };
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(event) || is_key_down(event, VirtualKeyCode::Escape) {
+                return Trans::Quit;
+            }
 
-    world.register::<CameraOrtho>();
+            if is_key_down(event, VirtualKeyCode::Return) {
+                return Trans::Push(Box::new(Game::default()));
+            }
+        }
 
-    transform.translation.z = 2.0;
-    transform.translation.x -= 256.0;
-    transform.translation.y -= 256.0;
-    transform.scale.x = 512.0;
-    transform.scale.y = 512.0;
-
-    world
-        .create_entity()
-        .with(CameraOrtho::normalized(CameraNormalizeMode::Contain))
-        .with(Camera::standard_2d())
-        .with(transform)
-        .build();
+        Trans::None
+    }
 }
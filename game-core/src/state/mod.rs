@@ -1,5 +1,17 @@
 mod game;
+mod game_over;
+mod load_failed;
+mod loading;
 mod menu;
+mod paused;
+mod rebind;
+mod victory;
 
 pub use self::game::Game;
+pub use self::game_over::GameOver;
+pub use self::load_failed::LoadFailed;
+pub use self::loading::Loading;
 pub use self::menu::Menu;
+pub use self::paused::Paused;
+pub use self::rebind::Rebind;
+pub use self::victory::Victory;
@@ -0,0 +1,34 @@
+use amethyst::{input::is_key_down, prelude::*, renderer::VirtualKeyCode};
+use crate::state::Rebind;
+
+/// Pushed on top of `Game`. The game's own dispatcher only runs from
+/// `Game::update`, so nothing moves while this is on the stack.
+pub struct Paused;
+
+impl<'a, 'b> SimpleState<'a, 'b> for Paused {
+    fn on_start(&mut self, _data: StateData<GameData>) {
+        info!("Paused, press Escape to resume or C to change controls");
+    }
+
+    fn on_resume(&mut self, _data: StateData<GameData>) {
+        info!("Paused, press Escape to resume or C to change controls");
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<GameData>,
+        event: StateEvent,
+    ) -> Trans<GameData<'a, 'b>, StateEvent> {
+        if let StateEvent::Window(event) = &event {
+            if is_key_down(event, VirtualKeyCode::Escape) {
+                return Trans::Pop;
+            }
+
+            if is_key_down(event, VirtualKeyCode::C) {
+                return Trans::Push(Box::new(Rebind::default()));
+            }
+        }
+
+        Trans::None
+    }
+}
@@ -0,0 +1,138 @@
+use amethyst::{
+    input::{get_key, is_key_down, InputHandler},
+    prelude::*,
+    renderer::VirtualKeyCode,
+    winit::ElementState,
+};
+use crate::controls::{Controls, GamepadState};
+
+#[derive(Debug, Clone, Copy)]
+enum Slot {
+    Up,
+    Down,
+    Left,
+    Right,
+    Fire,
+    GamepadFire,
+}
+
+/// The number key that picks each slot.
+const SLOTS: [(VirtualKeyCode, Slot); 6] = [
+    (VirtualKeyCode::Key1, Slot::Up),
+    (VirtualKeyCode::Key2, Slot::Down),
+    (VirtualKeyCode::Key3, Slot::Left),
+    (VirtualKeyCode::Key4, Slot::Right),
+    (VirtualKeyCode::Key5, Slot::Fire),
+    (VirtualKeyCode::Key6, Slot::GamepadFire),
+];
+
+/// Pushed on top of `Paused`. Pick a control by number, then press the key
+/// or gamepad button it should be. Changes are saved straight away.
+#[derive(Default)]
+pub struct Rebind {
+    // waiting for the new key or button of this slot
+    slot: Option<Slot>,
+}
+
+impl Rebind {
+    fn prompt(world: &World) {
+        let controls = world.read_resource::<Controls>();
+        let keyboard = &controls.keyboard;
+        info!("Controls, press a number to change one or Escape to go back:");
+        info!("1: up {:?}", keyboard.up);
+        info!("2: down {:?}", keyboard.down);
+        info!("3: left {:?}", keyboard.left);
+        info!("4: right {:?}", keyboard.right);
+        info!("5: fire {:?}", keyboard.fire);
+        info!("6: gamepad fire {:?}", controls.gamepad.fire);
+        info!("7: aim {:?}", controls.aim);
+    }
+
+    fn apply<F: FnOnce(&mut Controls)>(world: &World, change: F) {
+        let mut controls = world.write_resource::<Controls>();
+        change(&mut controls);
+
+        // the axes and actions are rebuilt from scratch, like at startup
+        world
+            .write_resource::<InputHandler<String, String>>()
+            .bindings = controls.keyboard.bindings();
+
+        if let Err(e) = controls.save() {
+            error!("{}", e);
+        }
+    }
+}
+
+impl<'a, 'b> SimpleState<'a, 'b> for Rebind {
+    fn on_start(&mut self, data: StateData<GameData>) {
+        Rebind::prompt(data.world);
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<GameData>,
+        event: StateEvent,
+    ) -> Trans<GameData<'a, 'b>, StateEvent> {
+        let event = match &event {
+            StateEvent::Window(event) => event,
+            _ => return Trans::None,
+        };
+
+        match self.slot {
+            None => {
+                if is_key_down(event, VirtualKeyCode::Escape) {
+                    return Trans::Pop;
+                }
+
+                if let Some((_, slot)) = SLOTS.iter().find(|(key, _)| is_key_down(event, *key)) {
+                    info!("Press the new {:?} control, or Escape to keep it", slot);
+                    self.slot = Some(*slot);
+                }
+
+                // only two choices, so no need to wait for another key
+                if is_key_down(event, VirtualKeyCode::Key7) {
+                    Rebind::apply(data.world, |c| c.aim = c.aim.next());
+                    Rebind::prompt(data.world);
+                }
+            }
+            Some(slot) => {
+                let key = match get_key(event) {
+                    Some((key, ElementState::Pressed)) => key,
+                    _ => return Trans::None,
+                };
+
+                match (slot, key) {
+                    (_, VirtualKeyCode::Escape) => {}
+                    // waits for a gamepad button in `update` instead
+                    (Slot::GamepadFire, _) => return Trans::None,
+                    (Slot::Up, key) => Rebind::apply(data.world, |c| c.keyboard.up = key),
+                    (Slot::Down, key) => Rebind::apply(data.world, |c| c.keyboard.down = key),
+                    (Slot::Left, key) => Rebind::apply(data.world, |c| c.keyboard.left = key),
+                    (Slot::Right, key) => Rebind::apply(data.world, |c| c.keyboard.right = key),
+                    (Slot::Fire, key) => Rebind::apply(data.world, |c| c.keyboard.fire = key),
+                }
+
+                self.slot = None;
+                Rebind::prompt(data.world);
+            }
+        }
+
+        Trans::None
+    }
+
+    fn update(&mut self, data: &mut StateData<GameData>) -> SimpleTrans<'a, 'b> {
+        if let Some(Slot::GamepadFire) = self.slot {
+            let button = {
+                let gamepad = data.world.read_resource::<GamepadState>();
+                gamepad.pressed.first().cloned()
+            };
+            if let Some(button) = button {
+                Rebind::apply(data.world, |c| c.gamepad.fire = button);
+                self.slot = None;
+                Rebind::prompt(data.world);
+            }
+        }
+
+        Trans::None
+    }
+}
@@ -5,7 +5,10 @@ use amethyst::{
     renderer::{SpriteRender, Transparent},
 };
 use config::GameoffConfig;
-use crate::component::{Ally, Animation, Motion, Player};
+use crate::assets::{AnimationId, Assets, SheetId};
+use crate::component::{Ally, Animation, Collider, Health, Motion, Path, Player};
+use crate::map::{MapSpawns, PassableTiles};
+use crate::pathfinding::next_waypoint;
 use rand::distributions::{Distribution, Uniform};
 
 pub struct Movement;
@@ -14,15 +17,26 @@ impl<'s> System<'s> for Movement {
     type SystemData = (
         ReadStorage<'s, Ally>,
         WriteStorage<'s, Motion>,
+        WriteStorage<'s, Path>,
         ReadStorage<'s, Transform>,
         ReadStorage<'s, Player>,
         Read<'s, GameoffConfig>,
+        Option<Read<'s, PassableTiles>>,
         Entities<'s>,
     );
 
     fn run(
         &mut self,
-        (allies, mut motions, transforms, players, config, entities): Self::SystemData,
+        (
+            allies,
+            mut motions,
+            mut paths,
+            transforms,
+            players,
+            config,
+            passable,
+            entities,
+        ): Self::SystemData,
     ) {
         let mut rng = rand::thread_rng();
         let zero_distance_dist = Uniform::new(0.5, 1.0);
@@ -35,14 +49,23 @@ impl<'s> System<'s> for Movement {
             (t.clone())
         };
 
-        for (_, motion, transform1, entity1) in
-            (&allies, &mut motions, &transforms, &entities).join()
+        for (_, motion, path, transform1, entity1) in
+            (&allies, &mut motions, &mut paths, &transforms, &entities).join()
         {
             let d = (p_transform.translation - transform1.translation).truncate();
             let m = d.magnitude().abs();
 
-            let mut pv = d.normalize(); //unit vector
-                                        //less than follow distance, do nothing
+            // head for the next tile on the route to the player, not through walls
+            let waypoint = next_waypoint(
+                path,
+                passable.as_ref().map(|p| &**p),
+                transform1.translation.truncate(),
+                p_transform.translation.truncate(),
+            );
+
+            let to_waypoint = waypoint - transform1.translation.truncate();
+            let mut pv = to_waypoint.normalize(); //unit vector
+                                                  //less than follow distance, do nothing
             if m < config.ally.follow_distance {
                 pv *= 0.0;
             } else if (m > config.ally.follow_distance) && (m < config.ally.max_distance) {
@@ -100,11 +123,15 @@ impl<'s> System<'s> for Grouper {
         ReadStorage<'s, Ally>,
         ReadStorage<'s, Transform>,
         WriteStorage<'s, Motion>,
+        WriteStorage<'s, Path>,
         ReadStorage<'s, Player>,
         Entities<'s>,
     );
 
-    fn run(&mut self, (allies, transforms, mut motions, players, entities): Self::SystemData) {
+    fn run(
+        &mut self,
+        (allies, transforms, mut motions, mut paths, players, entities): Self::SystemData,
+    ) {
         let p_transform = {
             let (t, _) = (&transforms, &players)
                 .join()
@@ -126,6 +153,7 @@ impl<'s> System<'s> for Grouper {
 
         for entity in merged {
             let _ = motions.insert(entity, Motion::default());
+            let _ = paths.insert(entity, Path::default());
         }
     }
 }
@@ -136,13 +164,16 @@ impl<'s> System<'s> for Spawner {
     type SystemData = (
         ReadStorage<'s, Player>,
         ReadStorage<'s, Motion>,
-        Read<'s, crate::load::LoadedTextures>,
+        Read<'s, Assets>,
         WriteStorage<'s, Transform>,
         WriteStorage<'s, Ally>,
         WriteStorage<'s, SpriteRender>,
         WriteStorage<'s, Transparent>,
         Entities<'s>,
         WriteStorage<'s, Animation>,
+        WriteStorage<'s, Collider>,
+        WriteStorage<'s, Health>,
+        Option<Read<'s, MapSpawns>>,
     );
 
     fn run(
@@ -150,15 +181,23 @@ impl<'s> System<'s> for Spawner {
         (
             players,
             motions,
-            textures,
+            assets,
             mut transforms,
             mut allies,
             mut sprites,
             mut transparent,
             entities,
             mut animation,
+            mut colliders,
+            mut healths,
+            spawns,
         ): Self::SystemData,
     ) {
+        // maps that place their own allies don't get random extras
+        if spawns.map_or(false, |spawns| !spawns.ally_pickups.is_empty()) {
+            return;
+        }
+
         let count = (&allies, !&motions).join().count();
 
         if count < 5 {
@@ -176,27 +215,31 @@ impl<'s> System<'s> for Spawner {
             }
 
             for pos in ally_positions {
+                let sprite = assets.sprite(&SheetId::Player, &AnimationId::Ally);
+                let (sprite_sheet, anim) = match sprite {
+                    Ok(sprite) => sprite,
+                    Err(e) => {
+                        error!("{}", e);
+                        return;
+                    }
+                };
+
                 let sprite = SpriteRender {
-                    sprite_sheet: textures.textures["FRONT.png"].clone(),
+                    sprite_sheet,
                     sprite_number: 1,
                     flip_horizontal: false,
                     flip_vertical: false,
                 };
 
-                let anim = Animation {
-                    total_frames: 8,
-                    max_count_till_next_frame: 0.1,
-                    frame_life_time_count: 0.1,
-                    current_frame: 0,
-                };
-
                 entities
                     .build_entity()
                     .with(pos, &mut transforms)
                     .with(Ally::default(), &mut allies)
+                    .with(Health::new(10), &mut healths)
                     .with(sprite, &mut sprites)
                     .with(Transparent, &mut transparent)
                     .with(anim, &mut animation)
+                    .with(Collider::from_sprite(32.0, 32.0, 0.5), &mut colliders)
                     .build();
             }
         }
@@ -1,9 +1,31 @@
 use amethyst::{
+    core::cgmath::{InnerSpace, Vector2},
     core::timing::Time,
-    ecs::{Entities, Join, Read, System, WriteStorage},
+    ecs::{
+        Entities, Entity, Join, Read, ReadStorage, ReaderId, Resources, System, SystemData, Write,
+        WriteStorage,
+    },
     renderer::SpriteRender,
+    shrev::EventChannel,
 };
-use crate::component::Animation;
+use crate::component::{Animation, ClipName, Health, Motion, Player};
+use crate::system::collision::HitEvent;
+
+/// Written when a one-shot clip reaches its last frame.
+#[derive(Debug, Clone)]
+pub struct AnimationEvent {
+    pub entity: Entity,
+    pub clip: ClipName,
+}
+
+/// Scales how fast every animation plays, below 1.0 for slow motion.
+pub struct AnimationTimeScale(pub f32);
+
+impl Default for AnimationTimeScale {
+    fn default() -> Self {
+        AnimationTimeScale(1.0)
+    }
+}
 
 pub struct Frame;
 
@@ -11,14 +33,104 @@ impl<'s> System<'s> for Frame {
     type SystemData = (
         WriteStorage<'s, SpriteRender>,
         WriteStorage<'s, Animation>,
+        Write<'s, EventChannel<AnimationEvent>>,
         Entities<'s>,
         Read<'s, Time>,
+        Read<'s, AnimationTimeScale>,
     );
 
-    fn run(&mut self, (mut sprite_render, mut animation, entities, time): Self::SystemData) {
-        for (_, animation, sprite_render) in (&entities, &mut animation, &mut sprite_render).join()
+    fn run(
+        &mut self,
+        (mut sprite_render, mut animation, mut events, entities, time, time_scale): Self::SystemData,
+    ) {
+        let seconds = time.delta_seconds() * time_scale.0;
+
+        for (entity, animation, sprite_render) in
+            (&entities, &mut animation, &mut sprite_render).join()
         {
-            animation.frame_update(sprite_render, time.delta_seconds());
+            if animation.frame_update(sprite_render, seconds) {
+                events.single_write(AnimationEvent {
+                    entity,
+                    clip: animation.clip,
+                });
+            }
+        }
+    }
+}
+
+/// Picks each entity's clip: the hurt and die actions when they apply,
+/// otherwise a walk cycle facing the way it moves, or idle.
+#[derive(Default)]
+pub struct Select {
+    reader: Option<ReaderId<HitEvent>>,
+}
+
+impl<'s> System<'s> for Select {
+    type SystemData = (
+        WriteStorage<'s, Animation>,
+        ReadStorage<'s, Motion>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Health>,
+        Read<'s, EventChannel<HitEvent>>,
+        Entities<'s>,
+    );
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.reader = Some(res.fetch_mut::<EventChannel<HitEvent>>().register_reader());
+    }
+
+    fn run(
+        &mut self,
+        (mut animations, motions, players, healths, hits, entities): Self::SystemData,
+    ) {
+        for hit in hits.read(self.reader.as_mut().unwrap()) {
+            if let Some(animation) = animations.get_mut(hit.target) {
+                animation.play(ClipName::Hurt);
+            }
+        }
+
+        for (entity, animation) in (&*entities, &mut animations).join() {
+            if healths.get(entity).map_or(false, |h| h.is_dead()) {
+                // once, holding the last frame rather than starting over
+                if animation.clip != ClipName::Die {
+                    animation.play(ClipName::Die);
+                }
+                continue;
+            }
+
+            if animation.is_busy() {
+                continue;
+            }
+
+            let heading = if let Some(player) = players.get(entity) {
+                if player.moving {
+                    Some(player.last_direction)
+                } else {
+                    None
+                }
+            } else {
+                motions
+                    .get(entity)
+                    .map(|motion| motion.vel)
+                    .filter(|vel| vel.magnitude2() > 1.0)
+            };
+
+            animation.play(heading.map_or(ClipName::Idle, walk_clip));
+        }
+    }
+}
+
+fn walk_clip(heading: Vector2<f32>) -> ClipName {
+    if heading.x.abs() > heading.y.abs() {
+        if heading.x > 0.0 {
+            ClipName::WalkRight
+        } else {
+            ClipName::WalkLeft
         }
+    } else if heading.y > 0.0 {
+        ClipName::WalkUp
+    } else {
+        ClipName::WalkDown
     }
 }
@@ -1,6 +1,7 @@
 use amethyst::core::cgmath::InnerSpace;
 use amethyst::core::cgmath::Vector2;
-use amethyst::renderer::Camera;
+use amethyst::renderer::{Camera, ScreenDimensions};
+use amethyst::utils::ortho_camera::CameraOrtho;
 use amethyst::{
     core::Transform,
     ecs::{Join, ReadStorage, System, WriteStorage},
@@ -37,3 +38,26 @@ impl<'s> System<'s> for Movement {
         }
     }
 }
+
+/// Turns a position in the window, in pixels from the top left, into world
+/// coordinates. The camera sits on the bottom left corner of the view scaled
+/// to its size, and `CameraOrtho` stretches the unit square of the view to
+/// the window's aspect ratio.
+pub fn screen_to_world(
+    ortho: &CameraOrtho,
+    transform: &Transform,
+    screen: &ScreenDimensions,
+    (x, y): (f64, f64),
+) -> Vector2<f32> {
+    let (left, right, bottom, top) = ortho.camera_offsets(screen.aspect_ratio());
+    let view = Vector2 {
+        x: left + (right - left) * x as f32 / screen.width(),
+        // window rows count down, the world counts up
+        y: top - (top - bottom) * y as f32 / screen.height(),
+    };
+
+    Vector2 {
+        x: transform.translation.x + view.x * transform.scale.x,
+        y: transform.translation.y + view.y * transform.scale.y,
+    }
+}
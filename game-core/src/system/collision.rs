@@ -0,0 +1,99 @@
+use amethyst::{
+    core::Transform,
+    ecs::{Entities, Entity, Join, ReadStorage, System, Write, WriteStorage},
+    shrev::EventChannel,
+};
+use crate::component::{Ally, Collider, Enemy, Health, Player, Projectile, Team};
+
+#[derive(Debug, Clone)]
+pub struct HitEvent {
+    pub projectile: Entity,
+    pub target: Entity,
+    pub damage: u32,
+}
+
+/// Finds projectiles overlapping a character of the opposing team, applies the
+/// projectile's damage and pops it. A `HitEvent` is only emitted when the
+/// damage lands, not when invulnerability frames soak it up.
+pub struct Detection;
+
+impl<'s> System<'s> for Detection {
+    type SystemData = (
+        WriteStorage<'s, Projectile>,
+        ReadStorage<'s, Collider>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Enemy>,
+        ReadStorage<'s, Ally>,
+        WriteStorage<'s, Health>,
+        Write<'s, EventChannel<HitEvent>>,
+        Entities<'s>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            mut projectiles,
+            colliders,
+            transforms,
+            players,
+            enemies,
+            allies,
+            mut healths,
+            mut hits,
+            entities,
+        ): Self::SystemData,
+    ) {
+        let mut contacts = vec![];
+
+        for (projectile, p_collider, p_transform, p_entity) in
+            (&projectiles, &colliders, &transforms, &*entities).join()
+        {
+            let p_pos = p_transform.translation.truncate();
+
+            for (collider, transform, entity) in (&colliders, &transforms, &*entities).join() {
+                let target_team = if enemies.contains(entity) {
+                    Team::Enemy
+                } else if players.contains(entity) || allies.contains(entity) {
+                    Team::Player
+                } else {
+                    continue;
+                };
+
+                if target_team == projectile.team
+                    || healths.get(entity).map_or(false, |h| h.is_dead())
+                {
+                    continue;
+                }
+
+                if p_collider.overlaps(p_pos, collider, transform.translation.truncate()) {
+                    contacts.push(HitEvent {
+                        projectile: p_entity,
+                        target: entity,
+                        damage: projectile.damage,
+                    });
+                    // a bubble pops on the first thing it touches
+                    break;
+                }
+            }
+        }
+
+        let mut events = vec![];
+        for contact in contacts {
+            // `projectile::Despawn` takes it from here
+            if let Some(projectile) = projectiles.get_mut(contact.projectile) {
+                projectile.lifetime = 0.0;
+            }
+
+            // dying is handled by the health systems
+            let landed = healths
+                .get_mut(contact.target)
+                .map_or(false, |health| health.damage(contact.damage));
+            if landed {
+                events.push(contact);
+            }
+        }
+
+        hits.iter_write(events);
+    }
+}
@@ -5,180 +5,155 @@ use amethyst::{
     ecs::{Entities, Join, Read, ReadStorage, System, WriteStorage},
     renderer::{SpriteRender, Transparent},
 };
-use crate::component::{Animation, Enemy, Motion, Player, Projectile};
-use rand::distributions::{Distribution, Uniform};
+use config::{AttackPattern, EnemyArchetypes, Weapons};
+use crate::assets::{AnimationId, Assets, SheetId};
+use crate::component::{
+    Ai, AiState, Animation, Collider, Enemy, Health, Motion, Path, Player, Team, Weapon,
+};
+use crate::error::Error;
+use crate::map::{MapSpawns, PassableTiles};
+use crate::pathfinding::next_waypoint;
+use rand::distributions::{Distribution, Uniform, WeightedIndex};
 use std::f32::consts::PI;
-use std::time::Duration;
 
-pub struct Movement {
-    pub random_movement_time: Duration,
-    pub random_idle_time: Duration,
-}
+/// Drives each enemy's `Ai` state machine from what it can see.
+pub struct Think;
 
-impl<'s> System<'s> for Movement {
+impl<'s> System<'s> for Think {
     type SystemData = (
         ReadStorage<'s, Player>,
-        WriteStorage<'s, Enemy>,
-        WriteStorage<'s, Motion>,
-        WriteStorage<'s, Transform>,
+        ReadStorage<'s, Enemy>,
+        ReadStorage<'s, Health>,
+        WriteStorage<'s, Ai>,
+        ReadStorage<'s, Transform>,
         Read<'s, Time>,
     );
 
-    fn run(&mut self, (players, mut enemies, mut motions, transforms, time): Self::SystemData) {
-        let idle_velocity = 50.0;
-        let tracking_velocity = 100.0;
-
-        let mut player_translation = Vector2 { x: 0.0, y: 0.0 };
-        let detect_radius = 180.0;
-        let detection_circle = Vector2 {
-            x: detect_radius,
-            y: detect_radius,
-        };
-
-        let time_accel = 4.0;
-        // let current_second = (time.absolute_time_seconds() * time_accel).floor();
+    fn run(&mut self, (players, enemies, healths, mut ais, transforms, time): Self::SystemData) {
+        let player_translation = (&players, &transforms)
+            .join()
+            .next()
+            .map(|(_, t)| t.translation.truncate());
 
-        // get player position
-        for (_, transform) in (&players, &transforms).join() {
-            player_translation = transform.translation.truncate();
-        }
+        let heading_range = Uniform::new_inclusive(0.0, 2.0 * PI);
+        let mut rng = rand::thread_rng();
 
-        for (enemy, motion, transform) in (&mut enemies, &mut motions, &transforms).join() {
-            let enemy_translation = transform.translation.truncate();
-            let player_direction = player_translation - enemy_translation;
+        for (enemy, health, ai, transform) in (&enemies, &healths, &mut ais, &transforms).join() {
+            ai.timer -= time.delta_seconds();
 
-            if player_direction.magnitude2() <= detection_circle.magnitude2() {
-                let enemy_shift = player_direction.normalize_to(tracking_velocity);
-                motion.vel = enemy_shift;
-                enemy.has_player_in_sight = true;
-            } else {
-                if motion.vel.magnitude2() > 0.0 {
-                    if let Some(diff) = self.random_movement_time.checked_sub(time.delta_time()) {
-                        self.random_movement_time = diff;
-                    } else {
-                        motion.vel = Vector2 { x: 0.0, y: 0.0 };
-                        self.random_idle_time = Duration::new(2, 0);
-                    }
-                }
+            let player_distance = player_translation
+                .map(|p| (p - transform.translation.truncate()).magnitude())
+                .filter(|d| *d <= enemy.detect_radius);
 
-                if motion.vel.magnitude2() == 0.0 {
-                    if let Some(diff) = self.random_idle_time.checked_sub(time.delta_time()) {
-                        self.random_idle_time = diff;
-                    } else {
-                        let range = Uniform::new_inclusive(0.0, 2.0 * PI);
-                        let mut rng = rand::thread_rng();
-                        let random_velocity = Vector2 {
-                            x: range.sample(&mut rng).sin(),
-                            y: range.sample(&mut rng).cos(),
-                        };
-                        motion.vel = random_velocity.normalize_to(idle_velocity);
-                        self.random_movement_time = Duration::new(2, 0);
-                    }
-                }
+            if let Some(distance) = player_distance {
+                let state = if (health.hp as f32) < health.max_hp as f32 * enemy.flee_below {
+                    AiState::Flee
+                } else if enemy.attack != AttackPattern::Passive && distance <= enemy.attack_range {
+                    AiState::Attack
+                } else {
+                    AiState::Chase
+                };
+                ai.set_engaged(state);
+                continue;
+            }
 
-                /*
-                if current_second % 2.0 == 0.0 {
-                    if motion.vel.magnitude2() == 0.0 {
-                        let range = Uniform::new_inclusive(0.0, 2.0 * PI);
-                        let mut rng = rand::thread_rng();
-                        let random_velocity = Vector2 {
-                            x: range.sample(&mut rng).sin(),
-                            y: range.sample(&mut rng).cos(),
-                        };
-                        motion.vel = random_velocity.normalize_to(idle_velocity);
-                    }
-                } else if current_second % 3.0 == 0.0 {
-                    motion.vel = Vector2 { x: 0.0, y: 0.0 };
+            match ai.state {
+                AiState::Chase | AiState::Attack | AiState::Flee => ai.set_idle(2.0),
+                AiState::Idle if ai.timer <= 0.0 => {
+                    let angle = heading_range.sample(&mut rng);
+                    let heading = Vector2 {
+                        x: angle.sin(),
+                        y: angle.cos(),
+                    };
+                    ai.set_wander(heading, 2.0);
                 }
-                */
-                enemy.has_player_in_sight = false;
+                AiState::Wander if ai.timer <= 0.0 => ai.set_idle(2.0),
+                _ => {}
             }
         }
     }
 }
 
-pub struct Attack;
+pub struct Movement;
 
-impl<'s> System<'s> for Attack {
+impl<'s> System<'s> for Movement {
     type SystemData = (
         ReadStorage<'s, Player>,
-        WriteStorage<'s, Enemy>,
-        WriteStorage<'s, Transform>,
-        Read<'s, crate::load::LoadedTextures>,
-        WriteStorage<'s, Projectile>,
+        ReadStorage<'s, Enemy>,
+        ReadStorage<'s, Ai>,
         WriteStorage<'s, Motion>,
-        WriteStorage<'s, SpriteRender>,
-        WriteStorage<'s, Transparent>,
-        WriteStorage<'s, Animation>,
-        Entities<'s>,
+        WriteStorage<'s, Path>,
+        ReadStorage<'s, Transform>,
+        Option<Read<'s, PassableTiles>>,
     );
 
     fn run(
         &mut self,
-        (
-            players,
-            mut enemies,
-            mut transforms,
-            textures,
-            mut projectiles,
-            mut motions,
-            mut sprites,
-            mut transparent,
-            mut animations,
-            entities,
-        ): Self::SystemData,
+        (players, enemies, ais, mut motions, mut paths, transforms, passable): Self::SystemData,
     ) {
-        let mut bubble_transform = None;
-        let mut bubble_dir = None;
-        for (_player, _p_transform) in (&players, &transforms).join() {
-            for (enemy, e_transform, e_motion) in (&mut enemies, &transforms, &motions).join() {
-                // if they're moving they shoot
-                if enemy.has_player_in_sight {
-                    bubble_transform = Some(e_transform.clone());
+        let mut player_translation = Vector2 { x: 0.0, y: 0.0 };
 
-                    let range = Uniform::new_inclusive(-5.0 * 32.0, 5.0 * 32.0);
-                    let mut rng = rand::thread_rng();
-                    let perp = e_motion.vel;
-                    let perp = perp.normalize_to(range.sample(&mut rng));
+        // get player position
+        for (_, transform) in (&players, &transforms).join() {
+            player_translation = transform.translation.truncate();
+        }
 
-                    bubble_dir = Some(e_motion.vel.normalize_to(32.0 * 23.0) + perp);
-                }
+        for (enemy, ai, motion, path, transform) in
+            (&enemies, &ais, &mut motions, &mut paths, &transforms).join()
+        {
+            let enemy_translation = transform.translation.truncate();
+            let player_direction = player_translation - enemy_translation;
 
-                // do some dmg stuff here maybe
-            }
+            motion.vel = match ai.state {
+                AiState::Idle => Vector2 { x: 0.0, y: 0.0 },
+                AiState::Wander => ai.heading * enemy.idle_speed,
+                _ if player_direction.magnitude2() == 0.0 => Vector2 { x: 0.0, y: 0.0 },
+                AiState::Chase | AiState::Attack => {
+                    // route around walls rather than straight at the player
+                    let waypoint = next_waypoint(
+                        path,
+                        passable.as_ref().map(|p| &**p),
+                        enemy_translation,
+                        player_translation,
+                    );
+                    (waypoint - enemy_translation).normalize_to(enemy.tracking_speed)
+                }
+                AiState::Flee => -player_direction.normalize_to(enemy.tracking_speed),
+            };
         }
+    }
+}
 
-        if let Some(transform) = bubble_transform {
-            let sprite = SpriteRender {
-                sprite_sheet: textures.textures["bubble.png"].clone(),
-                sprite_number: 0,
-                flip_horizontal: false,
-                flip_vertical: false,
-            };
+/// Pulls the trigger of every enemy that's attacking.
+pub struct Attack;
 
-            let anim = Animation {
-                total_frames: 2,
-                max_count_till_next_frame: 0.5,
-                frame_life_time_count: 0.5,
-                current_frame: 0,
-            };
+impl<'s> System<'s> for Attack {
+    type SystemData = (
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Enemy>,
+        ReadStorage<'s, Ai>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Motion>,
+        WriteStorage<'s, Weapon>,
+    );
 
-            let motion = Motion {
-                vel: bubble_dir.unwrap(),
-                acc: bubble_dir.unwrap() * -2.0,
-                min_vel: Some(32.0),
-                max_vel: None,
-            };
+    fn run(&mut self, (players, enemies, ais, transforms, motions, mut weapons): Self::SystemData) {
+        for (_player, p_transform) in (&players, &transforms).join() {
+            for (enemy, ai, e_transform, e_motion, weapon) in
+                (&enemies, &ais, &transforms, &motions, &mut weapons).join()
+            {
+                let heading = match enemy.attack {
+                    AttackPattern::Passive => continue,
+                    AttackPattern::Forward => e_motion.vel,
+                    AttackPattern::Aimed => {
+                        (p_transform.translation - e_transform.translation).truncate()
+                    }
+                };
 
-            entities
-                .build_entity()
-                .with(transform, &mut transforms)
-                .with(Projectile, &mut projectiles)
-                .with(motion, &mut motions)
-                .with(sprite, &mut sprites)
-                .with(Transparent, &mut transparent)
-                .with(anim, &mut animations)
-                .build();
+                if ai.state == AiState::Attack && heading.magnitude2() > 0.0 {
+                    weapon.trigger = Some(heading);
+                }
+            }
         }
     }
 }
@@ -188,7 +163,7 @@ pub struct Spawner;
 impl<'s> System<'s> for Spawner {
     type SystemData = (
         ReadStorage<'s, Player>,
-        Read<'s, crate::load::LoadedTextures>,
+        Read<'s, Assets>,
         WriteStorage<'s, Transform>,
         WriteStorage<'s, Enemy>,
         WriteStorage<'s, Motion>,
@@ -196,14 +171,22 @@ impl<'s> System<'s> for Spawner {
         WriteStorage<'s, Transparent>,
         Entities<'s>,
         WriteStorage<'s, Animation>,
-        Option<Read<'s, crate::map::PassableTiles>>,
+        WriteStorage<'s, Collider>,
+        WriteStorage<'s, Health>,
+        WriteStorage<'s, Ai>,
+        WriteStorage<'s, Path>,
+        Read<'s, EnemyArchetypes>,
+        WriteStorage<'s, Weapon>,
+        Read<'s, Weapons>,
+        Option<Read<'s, PassableTiles>>,
+        Option<Read<'s, MapSpawns>>,
     );
 
     fn run(
         &mut self,
         (
             players,
-            textures,
+            assets,
             mut transforms,
             mut enemies,
             mut motions,
@@ -211,59 +194,117 @@ impl<'s> System<'s> for Spawner {
             mut transparent,
             entities,
             mut animation,
+            mut colliders,
+            mut healths,
+            mut ais,
+            mut paths,
+            archetypes,
+            mut weapons,
+            weapon_defs,
             passable,
+            spawns,
         ): Self::SystemData,
     ) {
         let count = (&enemies).join().count();
+        let weights = archetypes.archetypes.iter().map(|a| a.weight);
+        let archetype_dist = match WeightedIndex::new(weights) {
+            Ok(dist) => dist,
+            Err(_) => return, // no archetypes to spawn
+        };
+
+        // spawners placed on the map take over from spawning around the player
+        let spawners = spawns
+            .as_ref()
+            .map_or(&[][..], |spawns| &spawns.enemy_spawners[..]);
+        let max_enemies = if spawners.is_empty() {
+            5
+        } else {
+            spawners.iter().map(|s| s.max as usize).sum()
+        };
 
         if let Some(passable) = passable {
-            if count < 5 {
+            if count < max_enemies {
                 let mut enemy_positions = vec![];
                 let range = Uniform::new_inclusive(-5.0 * 32.0, 5.0 * 32.0);
+                let idle_range = Uniform::new(0.0, 2.0);
                 let mut rng = rand::thread_rng();
-                for (_, transform) in (&players, &mut transforms).join() {
-                    let mut pos = Transform::default();
-                    pos.translation.x = transform.translation.x + range.sample(&mut rng);
-                    pos.translation.y = transform.translation.y + range.sample(&mut rng);
 
-                    // get tile and check if passable
-                    let tile_y = (pos.translation.y as u32 / 32) as usize;
-                    let tile_x = (pos.translation.x as u32 / 32) as usize;
+                let mut candidates = vec![];
+                if spawners.is_empty() {
+                    for (_, transform) in (&players, &transforms).join() {
+                        candidates.push(Vector2 {
+                            x: transform.translation.x + range.sample(&mut rng),
+                            y: transform.translation.y + range.sample(&mut rng),
+                        });
+                    }
+                } else {
+                    let area = &spawners[Uniform::new(0, spawners.len()).sample(&mut rng)].region;
+                    let x_range = Uniform::new_inclusive(-area.half_extents.x, area.half_extents.x);
+                    let y_range = Uniform::new_inclusive(-area.half_extents.y, area.half_extents.y);
+                    candidates.push(Vector2 {
+                        x: area.center.x + x_range.sample(&mut rng),
+                        y: area.center.y + y_range.sample(&mut rng),
+                    });
+                }
 
-                    if *passable
-                        .tile_matrix
-                        .get(tile_y)
-                        .and_then(|row| row.get(tile_x))
-                        .unwrap_or(&false)
-                    {
+                for candidate in candidates {
+                    if passable.is_area_passable(candidate, Vector2 { x: 16.0, y: 16.0 }) {
+                        let mut pos = Transform::default();
+                        pos.translation.x = candidate.x;
+                        pos.translation.y = candidate.y;
                         enemy_positions.push(pos);
                     }
                 }
 
                 for pos in enemy_positions {
+                    let archetype = &archetypes.archetypes[archetype_dist.sample(&mut rng)];
+
+                    let (sprite_sheet, anim) = match assets.sprite(
+                        &SheetId::Enemy(archetype.sheet.clone()),
+                        &AnimationId::Enemy(archetype.animation.clone()),
+                    ) {
+                        Ok(sprite) => sprite,
+                        Err(e) => {
+                            error!("{}", e);
+                            continue;
+                        }
+                    };
+
+                    let weapon = match &archetype.weapon {
+                        Some(name) => match weapon_defs.get(name) {
+                            Some(def) => Some(Weapon::new(def.clone(), Team::Enemy)),
+                            None => {
+                                error!("{}", Error::UnknownWeapon(name.clone()));
+                                continue;
+                            }
+                        },
+                        None => None,
+                    };
+
                     let sprite = SpriteRender {
-                        sprite_sheet: textures.textures["penguinFront.png"].clone(),
+                        sprite_sheet,
                         sprite_number: 0,
                         flip_horizontal: false,
                         flip_vertical: false,
                     };
 
-                    let anim = Animation {
-                        total_frames: 2,
-                        max_count_till_next_frame: 0.7,
-                        frame_life_time_count: 0.7,
-                        current_frame: 0,
-                    };
-
-                    entities
+                    let enemy = entities
                         .build_entity()
                         .with(pos, &mut transforms)
-                        .with(Enemy::default(), &mut enemies)
+                        .with(Enemy::from_archetype(archetype), &mut enemies)
+                        .with(Health::new(archetype.hp), &mut healths)
+                        .with(Ai::idle(idle_range.sample(&mut rng)), &mut ais)
+                        .with(Path::default(), &mut paths)
                         .with(Motion::default(), &mut motions)
                         .with(sprite, &mut sprites)
                         .with(Transparent, &mut transparent)
                         .with(anim, &mut animation)
+                        .with(Collider::from_sprite(32.0, 32.0, 1.0), &mut colliders)
                         .build();
+
+                    if let Some(weapon) = weapon {
+                        let _ = weapons.insert(enemy, weapon);
+                    }
                 }
             }
         }
@@ -0,0 +1,54 @@
+use amethyst::ecs::{Read, System, Write};
+use crate::controls::{Controls, GamepadState};
+
+/// Reads the first connected gamepad into `GamepadState`. Runs thread local,
+/// since gilrs can't be shared between threads.
+pub struct Poll {
+    // None when gamepads aren't supported here, the keyboard still works
+    gilrs: Option<gilrs::Gilrs>,
+}
+
+impl Default for Poll {
+    fn default() -> Self {
+        let gilrs = match gilrs::Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(e) => {
+                warn!("gamepads disabled: {}", e);
+                None
+            }
+        };
+
+        Poll { gilrs }
+    }
+}
+
+impl<'s> System<'s> for Poll {
+    type SystemData = (Read<'s, Controls>, Write<'s, GamepadState>);
+
+    fn run(&mut self, (controls, mut state): Self::SystemData) {
+        *state = GamepadState::default();
+
+        let gilrs = match self.gilrs.as_mut() {
+            Some(gilrs) => gilrs,
+            None => return,
+        };
+
+        // gilrs only updates gamepad state as events are taken out
+        while let Some(event) = gilrs.next_event() {
+            if let gilrs::EventType::ButtonPressed(button, _) = event.event {
+                state.pressed.push(button);
+            }
+        }
+
+        let bindings = &controls.gamepad;
+        if let Some((_, gamepad)) = gilrs.gamepads().next() {
+            state.movement = bindings.stick(
+                gamepad.value(bindings.move_x),
+                gamepad.value(bindings.move_y),
+            );
+            state.aim =
+                bindings.stick(gamepad.value(bindings.aim_x), gamepad.value(bindings.aim_y));
+            state.fire = gamepad.is_pressed(bindings.fire);
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use amethyst::{
+    core::timing::Time,
+    core::Transform,
+    ecs::{
+        Entities, Entity, Join, Read, ReadStorage, ReaderId, Resources, System, SystemData, Write,
+        WriteStorage,
+    },
+    renderer::{SpriteRender, Transparent},
+    shrev::EventChannel,
+};
+use crate::assets::{AnimationId, Assets, SheetId};
+use crate::component::{Ally, Animation, ClipName, Collider, Enemy, Health, Player};
+use crate::system::animation::AnimationEvent;
+use rand::distributions::{Bernoulli, Distribution};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone)]
+pub struct DeathEvent {
+    pub entity: Entity,
+}
+
+#[derive(Debug, Default)]
+pub struct Score {
+    pub kills: u32,
+    pub allies_lost: u32,
+    /// Set once the player's die clip has played.
+    pub player_dead: bool,
+    /// Set by `system::level::Exits` when the player reaches a goal.
+    pub player_won: bool,
+}
+
+/// Ticks invulnerability frames and regeneration, and emits a `DeathEvent` the
+/// first frame an entity's hp reaches zero.
+pub struct Update;
+
+impl<'s> System<'s> for Update {
+    type SystemData = (
+        WriteStorage<'s, Health>,
+        Write<'s, EventChannel<DeathEvent>>,
+        Read<'s, Time>,
+        Entities<'s>,
+    );
+
+    fn run(&mut self, (mut healths, mut deaths, time, entities): Self::SystemData) {
+        for (health, entity) in (&mut healths, &*entities).join() {
+            health.tick(time.delta_seconds());
+
+            if health.hp == 0 && !health.dead {
+                health.dead = true;
+                deaths.single_write(DeathEvent { entity });
+            }
+        }
+    }
+}
+
+/// Owns everything that happens when something dies: despawning once its die
+/// clip has played, drops and score keeping.
+#[derive(Default)]
+pub struct Death {
+    reader: Option<ReaderId<DeathEvent>>,
+    animation_reader: Option<ReaderId<AnimationEvent>>,
+    // dead, but still playing their die clip
+    dying: HashSet<Entity>,
+}
+
+impl<'s> System<'s> for Death {
+    type SystemData = (
+        Read<'s, EventChannel<DeathEvent>>,
+        Read<'s, EventChannel<AnimationEvent>>,
+        Write<'s, Score>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Enemy>,
+        WriteStorage<'s, Ally>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Health>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Transparent>,
+        WriteStorage<'s, Animation>,
+        WriteStorage<'s, Collider>,
+        Read<'s, Assets>,
+        Entities<'s>,
+    );
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.reader = Some(
+            res.fetch_mut::<EventChannel<DeathEvent>>()
+                .register_reader(),
+        );
+        self.animation_reader = Some(
+            res.fetch_mut::<EventChannel<AnimationEvent>>()
+                .register_reader(),
+        );
+    }
+
+    fn run(
+        &mut self,
+        (
+            deaths,
+            animation_events,
+            mut score,
+            players,
+            enemies,
+            mut allies,
+            mut transforms,
+            mut healths,
+            mut sprites,
+            mut transparent,
+            mut animations,
+            mut colliders,
+            assets,
+            entities,
+        ): Self::SystemData,
+    ) {
+        let mut drops = vec![];
+        let drop_chance = Bernoulli::new(0.3);
+        let mut rng = rand::thread_rng();
+        let mut gone = vec![];
+
+        for event in animation_events.read(self.animation_reader.as_mut().unwrap()) {
+            if event.clip == ClipName::Die && self.dying.remove(&event.entity) {
+                gone.push(event.entity);
+            }
+        }
+
+        for event in deaths.read(self.reader.as_mut().unwrap()) {
+            if enemies.contains(event.entity) {
+                score.kills += 1;
+                if drop_chance.sample(&mut rng) {
+                    if let Some(transform) = transforms.get(event.entity) {
+                        drops.push(transform.clone());
+                    }
+                }
+            } else if allies.contains(event.entity) {
+                score.allies_lost += 1;
+            }
+
+            let has_die_clip = animations
+                .get(event.entity)
+                .map_or(false, |animation| animation.set.finishes(ClipName::Die));
+            if has_die_clip {
+                self.dying.insert(event.entity);
+            } else {
+                gone.push(event.entity);
+            }
+        }
+
+        for entity in gone {
+            if players.contains(entity) {
+                // the player stays in the world so the camera and allies keep
+                // a target, it just stops responding to input
+                score.player_dead = true;
+            } else {
+                let _ = entities.delete(entity);
+            }
+        }
+
+        // rescued penguins show up as ally pickups
+        for mut pos in drops {
+            pos.scale.x = 0.5;
+            pos.scale.y = 0.5;
+
+            let (sprite_sheet, anim) = match assets.sprite(&SheetId::Player, &AnimationId::Ally) {
+                Ok(sprite) => sprite,
+                Err(e) => {
+                    error!("{}", e);
+                    return;
+                }
+            };
+
+            let sprite = SpriteRender {
+                sprite_sheet,
+                sprite_number: 1,
+                flip_horizontal: false,
+                flip_vertical: false,
+            };
+
+            entities
+                .build_entity()
+                .with(pos, &mut transforms)
+                .with(Ally::default(), &mut allies)
+                .with(Health::new(10), &mut healths)
+                .with(sprite, &mut sprites)
+                .with(Transparent, &mut transparent)
+                .with(anim, &mut animations)
+                .with(Collider::from_sprite(32.0, 32.0, 0.5), &mut colliders)
+                .build();
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use amethyst::{
+    core::Transform,
+    ecs::{Join, Read, ReadStorage, System, Write},
+};
+use crate::component::Player;
+use crate::level::{LevelChange, LevelTarget};
+use crate::map::MapSpawns;
+use crate::system::health::Score;
+
+/// Requests a level change when the player walks into an exit, and ends the
+/// game in a win when they walk into a goal.
+#[derive(Default)]
+pub struct Exits {
+    // set after a change so arriving on top of an exit doesn't bounce the
+    // player straight back
+    waiting_to_leave: bool,
+}
+
+impl<'s> System<'s> for Exits {
+    type SystemData = (
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Transform>,
+        Option<Read<'s, MapSpawns>>,
+        Write<'s, LevelChange>,
+        Write<'s, Score>,
+    );
+
+    fn run(&mut self, (players, transforms, spawns, mut change, mut score): Self::SystemData) {
+        let spawns = match spawns {
+            Some(spawns) => spawns,
+            None => return,
+        };
+
+        for (_, transform) in (&players, &transforms).join() {
+            let pos = transform.translation.truncate();
+            if spawns.goals.iter().any(|goal| goal.contains(pos)) {
+                score.player_won = true;
+            }
+
+            let exit = spawns.exits.iter().find(|exit| exit.region.contains(pos));
+
+            if self.waiting_to_leave {
+                self.waiting_to_leave = exit.is_some();
+                continue;
+            }
+
+            if let Some(exit) = exit {
+                change.target = Some(LevelTarget {
+                    level: exit.level.clone(),
+                    entry: exit.entry.clone(),
+                });
+                self.waiting_to_leave = true;
+            }
+        }
+    }
+}
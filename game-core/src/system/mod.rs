@@ -1,6 +1,13 @@
 pub mod ally;
 pub mod animation;
 pub mod camera;
+pub mod collision;
 pub mod enemy;
+pub mod gamepad;
+pub mod health;
+pub mod level;
 pub mod motion;
 pub mod player;
+pub mod projectile;
+pub mod tiles;
+pub mod weapon;
@@ -1,9 +1,10 @@
 use amethyst::{
     core::cgmath::{InnerSpace, Vector2},
     core::{timing::Time, Transform},
-    ecs::{Join, Read, System, WriteStorage},
+    ecs::{Entities, Join, Read, ReadStorage, System, WriteStorage},
 };
-use crate::component::Motion;
+use crate::component::{Collider, Motion};
+use crate::map::PassableTiles;
 
 pub struct Movement;
 
@@ -11,13 +12,19 @@ impl<'s> System<'s> for Movement {
     type SystemData = (
         WriteStorage<'s, Motion>,
         WriteStorage<'s, Transform>,
+        ReadStorage<'s, Collider>,
+        Option<Read<'s, PassableTiles>>,
         Read<'s, Time>,
+        Entities<'s>,
     );
 
-    fn run(&mut self, (mut motions, mut transforms, time): Self::SystemData) {
-        for (motion, transform) in (&mut motions, &mut transforms).join() {
+    fn run(
+        &mut self,
+        (mut motions, mut transforms, colliders, passable, time, entities): Self::SystemData,
+    ) {
+        for (motion, transform, entity) in (&mut motions, &mut transforms, &*entities).join() {
             let delta = time.delta_seconds();
-            let distance = motion.vel * delta + 0.5 * motion.acc * delta.powf(2.0); // d = v*t + (a*t^2)/2
+            let mut distance = motion.vel * delta + 0.5 * motion.acc * delta.powf(2.0); // d = v*t + (a*t^2)/2
             if let Some(min_vel) = motion.min_vel {
                 if distance.magnitude2() < (min_vel * delta).powf(2.0)
                     || motion.vel.dot(motion.vel + motion.acc * delta) < 0.0
@@ -36,7 +43,55 @@ impl<'s> System<'s> for Movement {
                 }
             }
             motion.vel += motion.acc * delta; // vo = vi + a*t
+
+            if let Some(passable) = &passable {
+                let half = colliders
+                    .get(entity)
+                    .map_or(Vector2 { x: 0.0, y: 0.0 }, |c| c.half_extents());
+                let pos = transform.translation.truncate();
+                distance = resolve_tiles(passable, motion, pos, half, distance);
+            }
+
             transform.translation += distance.extend(0.0);
         }
     }
 }
+
+/// Moves one axis at a time so things slide along walls instead of sticking
+/// to them. Blocked axes are stopped, or reflected if the motion bounces.
+pub fn resolve_tiles(
+    passable: &PassableTiles,
+    motion: &mut Motion,
+    pos: Vector2<f32>,
+    half: Vector2<f32>,
+    distance: Vector2<f32>,
+) -> Vector2<f32> {
+    // already overlapping a wall, e.g. spawned in one, let it walk out
+    if !passable.is_area_passable(pos, half) {
+        return distance;
+    }
+
+    let mut step = distance;
+
+    if !passable.is_area_passable(pos + Vector2 { x: step.x, y: 0.0 }, half) {
+        step.x = 0.0;
+        if motion.bounce {
+            motion.vel.x = -motion.vel.x;
+            motion.acc.x = -motion.acc.x;
+        } else {
+            motion.vel.x = 0.0;
+        }
+    }
+
+    if !passable.is_area_passable(pos + step, half) {
+        step.y = 0.0;
+        if motion.bounce {
+            motion.vel.y = -motion.vel.y;
+            motion.acc.y = -motion.acc.y;
+        } else {
+            motion.vel.y = 0.0;
+        }
+    }
+
+    step
+}
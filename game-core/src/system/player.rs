@@ -1,151 +1,144 @@
 use amethyst::{
     core::cgmath::{InnerSpace, Vector2},
-    core::Transform,
-    ecs::{Entities, Join, Read, ReadStorage, System, WriteStorage},
+    core::{timing::Time, Transform},
+    ecs::{Join, Read, ReadExpect, ReadStorage, System, WriteStorage},
     input::InputHandler,
-    renderer::{SpriteRender, Transparent},
+    renderer::ScreenDimensions,
+    utils::ortho_camera::CameraOrtho,
+    winit::MouseButton,
 };
-use crate::component::{Animation, Enemy, Motion, Player, Projectile};
-use rand::distributions::{Distribution, Uniform};
+use config::GameoffConfig;
+use crate::component::{Health, Motion, Player, Weapon};
+use crate::controls::{AimMode, Controls, GamepadState};
+use crate::system::camera::screen_to_world;
 
+/// Turns input into the player's `Motion`, which `motion::Movement` then
+/// carries out along with everything else that moves.
 pub struct Movement;
 
 impl<'s> System<'s> for Movement {
     type SystemData = (
         WriteStorage<'s, Player>,
-        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Motion>,
+        ReadStorage<'s, Health>,
         Read<'s, InputHandler<String, String>>,
-        Option<Read<'s, crate::map::PassableTiles>>,
+        Read<'s, GamepadState>,
+        Read<'s, GameoffConfig>,
+        Read<'s, Time>,
     );
 
-    fn run(&mut self, (mut players, mut transforms, input, passable): Self::SystemData) {
-        if let Some(passable) = passable {
-            let x_move = input.axis_value("entity_x").unwrap();
-            let y_move = input.axis_value("entity_y").unwrap();
+    fn run(
+        &mut self,
+        (mut players, mut motions, healths, input, gamepad, config, time): Self::SystemData,
+    ) {
+        let tuning = &config.player;
+        let delta = time.delta_seconds();
+
+        // unbound axes just don't move the player
+        let mut direction = gamepad.movement
+            + Vector2 {
+                x: input.axis_value("entity_x").unwrap_or(0.0) as f32,
+                y: input.axis_value("entity_y").unwrap_or(0.0) as f32,
+            };
+        if tuning.normalize_diagonal && direction.magnitude2() > 1.0 {
+            direction = direction.normalize();
+        }
 
-            for (player, transform) in (&mut players, &mut transforms).join() {
-                if x_move != 0.0 || y_move != 0.0 {
-                    player.last_direction = Vector2 {
-                        x: x_move as f32,
-                        y: y_move as f32,
-                    };
-                }
+        for (player, motion, health) in (&mut players, &mut motions, &healths).join() {
+            player.moving = !health.is_dead() && direction.magnitude2() > 0.0;
 
-                let goal_x = transform.translation.x + x_move as f32 * 5.0;
-                let goal_y = transform.translation.y + y_move as f32 * 5.0;
+            let (target, rate) = if player.moving {
+                player.last_direction = direction;
+                (direction * tuning.max_speed, tuning.acceleration)
+            } else {
+                (Vector2 { x: 0.0, y: 0.0 }, tuning.friction)
+            };
+            let vel = approach(motion.vel, target, rate * delta);
+
+            // handed over as an acceleration so `motion::Movement` integrates
+            // the change across the frame, and the path doesn't depend on
+            // how many frames it's split into
+            motion.acc = if delta > 0.0 {
+                (vel - motion.vel) / delta
+            } else {
+                Vector2 { x: 0.0, y: 0.0 }
+            };
+        }
+    }
+}
 
-                let tile_y = (goal_y as u32 / 32) as usize;
-                let tile_x = (goal_x as u32 / 32) as usize;
+/// Moves `from` towards `to` by at most `max_step`.
+fn approach(from: Vector2<f32>, to: Vector2<f32>, max_step: f32) -> Vector2<f32> {
+    let difference = to - from;
+    let distance = difference.magnitude();
 
-                if *passable
-                    .tile_matrix
-                    .get(tile_y)
-                    .and_then(|row| row.get(tile_x))
-                    .unwrap_or(&false)
-                {
-                    transform.translation.x = goal_x;
-                    transform.translation.y = goal_y;
-                }
-            }
-        }
+    if distance <= max_step {
+        to
+    } else {
+        from + difference * (max_step / distance)
     }
 }
 
+/// Pulls the player's trigger, aimed with the gamepad, the mouse or the way
+/// they last walked.
 pub struct Attack;
 
 impl<'s> System<'s> for Attack {
     type SystemData = (
         ReadStorage<'s, Player>,
-        WriteStorage<'s, Enemy>,
-        WriteStorage<'s, Transform>,
-        Read<'s, crate::load::LoadedTextures>,
-        WriteStorage<'s, Projectile>,
-        WriteStorage<'s, Motion>,
-        WriteStorage<'s, SpriteRender>,
-        WriteStorage<'s, Transparent>,
-        WriteStorage<'s, Animation>,
-        Entities<'s>,
+        ReadStorage<'s, Transform>,
+        WriteStorage<'s, Weapon>,
+        ReadStorage<'s, Health>,
         Read<'s, InputHandler<String, String>>,
+        Read<'s, GamepadState>,
+        Read<'s, Controls>,
+        ReadStorage<'s, CameraOrtho>,
+        ReadExpect<'s, ScreenDimensions>,
     );
 
     fn run(
         &mut self,
         (
             players,
-            mut enemies,
-            mut transforms,
-            textures,
-            mut projectiles,
-            mut motions,
-            mut sprites,
-            mut transparent,
-            mut animations,
-            entities,
+            transforms,
+            mut weapons,
+            healths,
             input,
+            gamepad,
+            controls,
+            orthos,
+            screen,
         ): Self::SystemData,
     ) {
-        let mut bubble_transform = None;
-        let mut bubble_dir = None;
-        for (player, p_transform) in (&players, &transforms).join() {
-            for (enemy, e_transform, enemy_entity) in (&mut enemies, &transforms, &*entities).join()
-            {
-                if input.action_is_down("jump") == Some(true) {
-                    bubble_transform = Some(p_transform.clone());
-
-                    let range = Uniform::new_inclusive(-5.0 * 32.0, 5.0 * 32.0);
-                    let mut rng = rand::thread_rng();
-                    let perp = Vector2 {
-                        x: player.last_direction.y,
-                        y: -player.last_direction.x,
-                    };
-                    let perp = perp.normalize_to(range.sample(&mut rng));
-
-                    bubble_dir = Some(player.last_direction.normalize_to(32.0 * 23.0) + perp);
-                }
-
-                if e_transform.translation.x < p_transform.translation.x
-                    && e_transform.translation.y < p_transform.translation.y
-                {
-                    if enemy.hp > 0 {
-                        enemy.hp -= 1;
-                    } else {
-                        let _r = entities.delete(enemy_entity);
-                    }
-                }
+        let mouse_aim = controls.aim == AimMode::Mouse;
+        let clicking = mouse_aim && input.mouse_button_is_down(MouseButton::Left);
+        let cursor = match ((&orthos, &transforms).join().next(), input.mouse_position()) {
+            (Some((ortho, transform)), Some(position)) if mouse_aim => {
+                Some(screen_to_world(ortho, transform, &screen, position))
+            }
+            _ => None,
+        };
+
+        for (player, p_transform, health, weapon) in
+            (&players, &transforms, &healths, &mut weapons).join()
+        {
+            // twin-stick: pushing the aim stick fires along it
+            let aiming = gamepad.aim.magnitude2() > 0.0;
+            let firing =
+                aiming || clicking || gamepad.fire || input.action_is_down("fire") == Some(true);
+
+            if health.is_dead() || !firing {
+                continue;
             }
-        }
-
-        if let Some(transform) = bubble_transform {
-            let sprite = SpriteRender {
-                sprite_sheet: textures.textures["bubble.png"].clone(),
-                sprite_number: 0,
-                flip_horizontal: false,
-                flip_vertical: false,
-            };
-
-            let anim = Animation {
-                total_frames: 2,
-                max_count_till_next_frame: 0.5,
-                frame_life_time_count: 0.5,
-                current_frame: 0,
-            };
-
-            let motion = Motion {
-                vel: bubble_dir.unwrap(),
-                acc: bubble_dir.unwrap() * -2.0,
-                min_vel: Some(32.0),
-                max_vel: None,
-            };
 
-            entities
-                .build_entity()
-                .with(transform, &mut transforms)
-                .with(Projectile, &mut projectiles)
-                .with(motion, &mut motions)
-                .with(sprite, &mut sprites)
-                .with(Transparent, &mut transparent)
-                .with(anim, &mut animations)
-                .build();
+            let towards_cursor = cursor
+                .map(|cursor| cursor - p_transform.translation.truncate())
+                .filter(|towards| towards.magnitude2() > 0.0);
+            weapon.trigger = Some(match towards_cursor {
+                _ if aiming => gamepad.aim,
+                Some(towards) => towards,
+                None => player.last_direction,
+            });
         }
     }
 }
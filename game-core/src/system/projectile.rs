@@ -0,0 +1,153 @@
+use amethyst::{
+    core::cgmath::{InnerSpace, Vector2},
+    core::{timing::Time, Transform},
+    ecs::{Entities, EntitiesRes, Entity, Join, Read, ReadStorage, System, Write, WriteStorage},
+    renderer::{Camera, DisplayConfig, SpriteRender, Transparent, WindowMessages},
+};
+use crate::component::{Animation, Collider, Motion, Projectile};
+use crate::map::PassableTiles;
+
+/// How far past the edges of the view projectiles may fly, in view sizes.
+/// Enemies a little way off screen can still shoot in.
+const VIEW_MARGIN: f32 = 1.0;
+
+/// Projectile entities that are done with, stripped of their components.
+/// `weapon::Fire` hands them out again before creating new ones, so long
+/// sessions settle on a steady number of entities.
+#[derive(Default)]
+pub struct ProjectilePool {
+    free: Vec<Entity>,
+    /// Projectiles in flight as of the last `Despawn`.
+    pub live: usize,
+    /// Shows the live and pooled counts in the window title, and logs them,
+    /// every second. Toggled with F3 in game.
+    pub show_count: bool,
+}
+
+impl ProjectilePool {
+    /// A pooled entity, or a new one if the pool is empty.
+    pub fn take(&mut self, entities: &EntitiesRes) -> Entity {
+        // changing level deletes everything, pooled entities included
+        while let Some(entity) = self.free.pop() {
+            if entities.is_alive(entity) {
+                return entity;
+            }
+        }
+
+        entities.create()
+    }
+
+    pub fn pooled(&self) -> usize {
+        self.free.len()
+    }
+}
+
+/// Returns projectiles to the pool once they've popped, run out of time or
+/// range, or left the map or the area around the view.
+#[derive(Default)]
+pub struct Despawn {
+    // time since the live count was last shown
+    report_timer: f32,
+    // the window title is the count, and needs putting back when it's off
+    showing_count: bool,
+}
+
+impl<'s> System<'s> for Despawn {
+    type SystemData = (
+        WriteStorage<'s, Projectile>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Motion>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Transparent>,
+        WriteStorage<'s, Animation>,
+        WriteStorage<'s, Collider>,
+        ReadStorage<'s, Camera>,
+        Option<Read<'s, PassableTiles>>,
+        Write<'s, ProjectilePool>,
+        Entities<'s>,
+        Read<'s, Time>,
+        Write<'s, WindowMessages>,
+        Option<Read<'s, DisplayConfig>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            mut projectiles,
+            mut transforms,
+            mut motions,
+            mut sprites,
+            mut transparent,
+            mut animations,
+            mut colliders,
+            cameras,
+            passable,
+            mut pool,
+            entities,
+            time,
+            mut window,
+            display,
+        ): Self::SystemData,
+    ) {
+        let delta = time.delta_seconds();
+
+        // the camera sits on the bottom left corner of the view, scaled to
+        // its size
+        let view = (&cameras, &transforms).join().next().map(|(_, transform)| {
+            let min = transform.translation.truncate();
+            let size = transform.scale.truncate();
+            (min - size * VIEW_MARGIN, min + size * (1.0 + VIEW_MARGIN))
+        });
+        let map = passable.as_ref().map(|passable| passable.size());
+
+        let mut done = vec![];
+        for (projectile, transform, entity) in (&mut projectiles, &transforms, &*entities).join() {
+            projectile.lifetime -= delta;
+
+            let pos = transform.translation.truncate();
+            let out_of_range = projectile.range.map_or(false, |range| {
+                (pos - projectile.origin).magnitude2() > range * range
+            });
+            let off_map = map.map_or(false, |size| !inside(pos, Vector2 { x: 0.0, y: 0.0 }, size));
+            let off_view = view.map_or(false, |(min, max)| !inside(pos, min, max));
+
+            if projectile.lifetime <= 0.0 || out_of_range || off_map || off_view {
+                done.push(entity);
+            }
+        }
+
+        for entity in done {
+            projectiles.remove(entity);
+            transforms.remove(entity);
+            motions.remove(entity);
+            sprites.remove(entity);
+            transparent.remove(entity);
+            animations.remove(entity);
+            colliders.remove(entity);
+            pool.free.push(entity);
+        }
+
+        pool.live = (&projectiles).join().count();
+
+        if pool.show_count {
+            self.report_timer += delta;
+            if self.report_timer >= 1.0 || !self.showing_count {
+                self.report_timer = 0.0;
+                self.showing_count = true;
+
+                let count = format!("{} projectiles live, {} pooled", pool.live, pool.pooled());
+                info!("{}", count);
+                window.send_command(move |window| window.set_title(&count));
+            }
+        } else if self.showing_count {
+            self.showing_count = false;
+
+            let title = display.map_or_else(String::new, |display| display.title.clone());
+            window.send_command(move |window| window.set_title(&title));
+        }
+    }
+}
+
+fn inside(pos: Vector2<f32>, min: Vector2<f32>, max: Vector2<f32>) -> bool {
+    pos.x >= min.x && pos.y >= min.y && pos.x <= max.x && pos.y <= max.y
+}
@@ -0,0 +1,85 @@
+use amethyst::{
+    core::cgmath::Vector2,
+    core::Transform,
+    ecs::{Entities, Join, ReadStorage, System, Write, WriteStorage},
+    renderer::{Camera, SpriteRender},
+};
+use crate::map::TileChunks;
+use std::collections::HashSet;
+
+/// Spawns the map chunks around the camera and deletes the ones it has left
+/// behind, so the number of tile entities depends on the view and not on the
+/// size of the map.
+pub struct Chunks;
+
+impl<'s> System<'s> for Chunks {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Camera>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, SpriteRender>,
+        Write<'s, TileChunks>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, cameras, mut transforms, mut sprites, mut tiles): Self::SystemData,
+    ) {
+        // the camera sits on the bottom left corner of the view, scaled to
+        // its size
+        let view = (&cameras, &transforms).join().next().map(|(_, transform)| {
+            let min = transform.translation.truncate();
+            (min, min + transform.scale.truncate())
+        });
+        let (min, max) = match view {
+            Some(view) => view,
+            None => return,
+        };
+
+        // a chunk of slack on every side covers the view stretching to fit
+        // the window, and spawns chunks before they scroll in
+        let tiles = &mut *tiles;
+        let slack = Vector2 {
+            x: tiles.chunk_width,
+            y: tiles.chunk_height,
+        };
+        let wanted: HashSet<_> = tiles
+            .overlapping(min - slack, max + slack)
+            .into_iter()
+            .collect();
+
+        let stale: Vec<_> = tiles.spawned.difference(&wanted).cloned().collect();
+        for key in stale {
+            tiles.spawned.remove(&key);
+            if let Some(chunk) = tiles.chunks.get_mut(&key) {
+                for entity in chunk.entities.drain(..) {
+                    let _ = entities.delete(entity);
+                }
+            }
+        }
+
+        for key in wanted {
+            if tiles.spawned.contains(&key) {
+                continue;
+            }
+
+            let chunk = match tiles.chunks.get_mut(&key) {
+                Some(chunk) => chunk,
+                None => continue,
+            };
+
+            for tile in &chunk.tiles {
+                let mut transform = Transform::default();
+                transform.translation = tile.translation;
+
+                let entity = entities
+                    .build_entity()
+                    .with(transform, &mut transforms)
+                    .with(tile.sprite.clone(), &mut sprites)
+                    .build();
+                chunk.entities.push(entity);
+            }
+            tiles.spawned.insert(key);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use amethyst::{
+    core::cgmath::{InnerSpace, Vector2},
+    core::{timing::Time, Transform},
+    ecs::{Entities, Join, Read, System, Write, WriteStorage},
+    renderer::{SpriteRender, Transparent},
+};
+use crate::assets::{AnimationId, Assets, SheetId};
+use crate::component::weapon::rotate;
+use crate::component::{Animation, ClipName, Collider, Motion, Projectile, Weapon};
+use crate::system::projectile::ProjectilePool;
+use rand::distributions::{Distribution, Uniform};
+
+/// A projectile to spawn, worked out while the weapons are borrowed.
+struct Shot {
+    transform: Transform,
+    velocity: Vector2<f32>,
+    drag: f32,
+    projectile: Projectile,
+    sprite: SpriteRender,
+    animation: Animation,
+}
+
+/// Fires every weapon whose trigger is held, at the rate and in the pattern
+/// its definition asks for.
+pub struct Fire;
+
+impl<'s> System<'s> for Fire {
+    type SystemData = (
+        WriteStorage<'s, Weapon>,
+        WriteStorage<'s, Transform>,
+        Read<'s, Assets>,
+        WriteStorage<'s, Projectile>,
+        WriteStorage<'s, Motion>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Transparent>,
+        WriteStorage<'s, Animation>,
+        WriteStorage<'s, Collider>,
+        Entities<'s>,
+        Write<'s, ProjectilePool>,
+        Read<'s, Time>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            mut weapons,
+            mut transforms,
+            assets,
+            mut projectiles,
+            mut motions,
+            mut sprites,
+            mut transparent,
+            mut animations,
+            mut colliders,
+            entities,
+            mut pool,
+            time,
+        ): Self::SystemData,
+    ) {
+        let mut rng = rand::thread_rng();
+        let mut shots = vec![];
+
+        for (weapon, transform, animation) in (&mut weapons, &transforms, &mut animations).join() {
+            let aim = match weapon.update(time.delta_seconds()) {
+                Some(aim) if aim.magnitude2() > 0.0 => aim.normalize(),
+                _ => continue,
+            };
+            animation.play(ClipName::Attack);
+
+            let def = &weapon.def;
+            let (sprite_sheet, anim) = match assets.sprite(
+                &SheetId::Projectile(def.sheet.clone()),
+                &AnimationId::Projectile(def.animation.clone()),
+            ) {
+                Ok(sprite) => sprite,
+                Err(e) => {
+                    error!("{}", e);
+                    continue;
+                }
+            };
+
+            let jitter = Uniform::new_inclusive(-def.jitter, def.jitter);
+            for turn in weapon.pattern() {
+                let direction = rotate(aim, turn + jitter.sample(&mut rng));
+
+                shots.push(Shot {
+                    transform: transform.clone(),
+                    velocity: direction * def.speed,
+                    drag: def.drag,
+                    projectile: Projectile {
+                        team: weapon.team,
+                        damage: def.damage,
+                        lifetime: def.lifetime,
+                        origin: transform.translation.truncate(),
+                        range: def.range,
+                    },
+                    sprite: SpriteRender {
+                        sprite_sheet: sprite_sheet.clone(),
+                        sprite_number: 0,
+                        flip_horizontal: false,
+                        flip_vertical: false,
+                    },
+                    animation: anim.clone(),
+                });
+            }
+        }
+
+        for shot in shots {
+            let motion = Motion {
+                vel: shot.velocity,
+                acc: shot.velocity * -shot.drag,
+                min_vel: Some(32.0),
+                max_vel: None,
+                bounce: true,
+            };
+
+            // `take` only hands out live entities, so inserting can't fail
+            let entity = pool.take(&entities);
+            let _ = transforms.insert(entity, shot.transform);
+            let _ = projectiles.insert(entity, shot.projectile);
+            let _ = motions.insert(entity, motion);
+            let _ = sprites.insert(entity, shot.sprite);
+            let _ = transparent.insert(entity, Transparent);
+            let _ = animations.insert(entity, shot.animation);
+            let _ = colliders.insert(entity, Collider::from_sprite(32.0, 32.0, 1.0));
+        }
+    }
+}
@@ -38,8 +38,16 @@
  <tile id="27" terrain="0,3,0,0"/>
  <tile id="28" terrain="3,0,0,0"/>
  <tile id="29" terrain="3,3,3,3"/>
- <tile id="30" terrain="4,4,4,4"/>
- <tile id="31" terrain="4,4,4,4"/>
+ <tile id="30" terrain="4,4,4,4">
+  <properties>
+   <property name="solid" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="31" terrain="4,4,4,4">
+  <properties>
+   <property name="solid" type="bool" value="true"/>
+  </properties>
+ </tile>
  <tile id="32" terrain="3,1,3,1"/>
  <tile id="33" terrain="1,1,1,1"/>
  <tile id="34" terrain="1,3,1,3"/>
@@ -0,0 +1,97 @@
+//! The files game-core reads: amethyst sprite sheets and animation clips.
+
+use failure::Error;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// How long a frame is shown when the source has no timing, as with Piskel.
+pub const DEFAULT_FRAME_DURATION: f32 = 0.1;
+
+#[derive(Debug, Serialize)]
+pub struct AmethystSheet {
+    pub spritesheet_width: f64,
+    pub spritesheet_height: f64,
+    pub sprites: Vec<AmethystSprite>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AmethystSprite {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    /// Pixels to shift the sprite left and down so a trimmed frame still
+    /// lines up with its untrimmed neighbours.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offsets: Option<[f64; 2]>,
+}
+
+/// Mirrors `component::animation::ClipName` in game-core.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum ClipName {
+    Idle,
+    WalkUp,
+    WalkDown,
+    WalkLeft,
+    WalkRight,
+    Attack,
+    Hurt,
+    Die,
+}
+
+impl ClipName {
+    /// Accepts `walk_down`, `walk-down`, `WalkDown` and so on.
+    pub fn parse(name: &str) -> Option<ClipName> {
+        let name: String = name
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(|c| c.to_lowercase())
+            .collect();
+
+        Some(match name.as_str() {
+            "idle" => ClipName::Idle,
+            "walkup" => ClipName::WalkUp,
+            "walkdown" => ClipName::WalkDown,
+            "walkleft" => ClipName::WalkLeft,
+            "walkright" => ClipName::WalkRight,
+            "attack" => ClipName::Attack,
+            "hurt" => ClipName::Hurt,
+            "die" => ClipName::Die,
+            _ => return None,
+        })
+    }
+}
+
+/// Mirrors `component::animation::ClipMode` in game-core.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ClipMode {
+    Loop,
+    Once,
+    PingPong,
+}
+
+impl ClipMode {
+    fn is_loop(&self) -> bool {
+        *self == ClipMode::Loop
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AmethystClip {
+    pub frames: Vec<(usize, f32)>,
+    /// Left out when looping, which is what game-core defaults to.
+    #[serde(skip_serializing_if = "ClipMode::is_loop")]
+    pub mode: ClipMode,
+}
+
+/// Same layout as `component::animation::AnimationSet` in game-core.
+#[derive(Debug, Serialize)]
+pub struct AmethystAnimations {
+    pub clips: BTreeMap<ClipName, AmethystClip>,
+}
+
+/// Pretty RON, the way the committed resources are formatted.
+pub fn to_ron<T: Serialize>(value: &T) -> Result<String, Error> {
+    let ron = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())?;
+    Ok(format!("{}\n", ron))
+}
@@ -1,95 +1,291 @@
 #[macro_use]
+extern crate failure;
+#[macro_use]
 extern crate serde_derive;
+#[macro_use]
+extern crate structopt;
 
-extern crate failure;
+extern crate image;
+extern crate notify;
 extern crate serde;
 extern crate serde_json;
+extern crate tilesheet;
+
+mod amethyst;
+mod names;
+mod pack;
+mod texturepacker;
+mod tileset;
+
+use failure::{err_msg, Error};
+use notify::{DebouncedEvent, RecursiveMode, Watcher};
+use pack::PackOpt;
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+use std::time::Duration;
+use structopt::clap::AppSettings;
+use structopt::StructOpt;
+use texturepacker::{Format, JsonSheet};
+use tileset::TilesetOpt;
+
+/// Converts Piskel, Aseprite and TexturePacker JSON exports into amethyst
+/// sprite sheet RON.
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "piskel2amethyst",
+    raw(settings = "&[AppSettings::SubcommandsNegateReqs, AppSettings::ArgsNegateSubcommands]")
+)]
+struct Opt {
+    /// JSON exports, or directories to convert every JSON file in
+    #[structopt(parse(from_os_str), required = true)]
+    inputs: Vec<PathBuf>,
+
+    /// Where to write each input's RON, in the same order as the inputs. `-`
+    /// prints to stdout. Defaults to <name>.ron next to the JSON.
+    #[structopt(
+        short = "o",
+        long = "output",
+        parse(from_os_str),
+        raw(number_of_values = "1")
+    )]
+    outputs: Vec<PathBuf>,
 
-use failure::Error;
-use std::collections::HashMap;
-use std::fs::File;
+    /// Editor that wrote the JSON, detected from its `meta.app` if not given
+    #[structopt(
+        short = "f",
+        long = "format",
+        raw(possible_values = "Format::NAMES", case_insensitive = "true")
+    )]
+    format: Option<Format>,
 
-#[derive(Debug, Deserialize)]
-struct PiskelSheet {
-    frames: HashMap<String, PiskelImage>,
-    meta: PiskelMeta,
+    /// Also write animation clips to <name>.anim.ron next to each sheet
+    #[structopt(short = "a", long = "animations")]
+    animations: bool,
+
+    /// Don't write anything, fail if any output is missing or out of date
+    #[structopt(long = "check", conflicts_with = "watch")]
+    check: bool,
+
+    /// Convert once, then again every time an input changes
+    #[structopt(short = "w", long = "watch")]
+    watch: bool,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Packs a directory of frame PNGs into an atlas and its sprite sheet
+    #[structopt(name = "pack")]
+    Pack(PackOpt),
+    /// Writes the sprite sheet of a Tiled tileset as RON
+    #[structopt(name = "tileset")]
+    Tileset(TilesetOpt),
 }
 
-#[allow(non_snake_case)]
-#[derive(Debug, Deserialize)]
-struct PiskelImage {
-    frame: Piskelxywh,
-    rotated: bool,
-    trimmed: bool,
-    spriteSourceSize: Piskelxywh,
-    sourceSize: Piskelwh,
+/// One JSON file and where its sheet goes.
+struct Job {
+    input: PathBuf,
+    output: PathBuf,
 }
 
-#[derive(Debug, Deserialize)]
-struct PiskelMeta {
-    app: String,
-    version: String,
-    image: String,
-    format: String,
-    size: Piskelwh,
+impl Job {
+    fn new(input: PathBuf, output: Option<PathBuf>) -> Self {
+        let output = output.unwrap_or_else(|| input.with_extension("ron"));
+        Job { input, output }
+    }
+
+    fn animations_output(&self) -> PathBuf {
+        if self.output == Path::new("-") {
+            return self.output.clone();
+        }
+        self.output.with_extension("anim.ron")
+    }
+
+    /// The generated files and their contents.
+    fn generate(&self, opt: &Opt) -> Result<Vec<(PathBuf, String)>, Error> {
+        let json = JsonSheet::read(File::open(&self.input)?)?;
+        let format = opt.format.unwrap_or_else(|| json.detect_format());
+        let (sheet, clips) = json.convert(format);
+
+        let mut files = vec![(self.output.clone(), amethyst::to_ron(&sheet)?)];
+        if opt.animations {
+            files.push((self.animations_output(), amethyst::to_ron(&clips)?));
+        }
+
+        Ok(files)
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension() == Some(OsStr::new("json"))
+}
+
+/// Expands directories into the JSON files directly inside them, sorted so
+/// the order doesn't depend on the file system.
+fn expand_inputs(inputs: &[PathBuf]) -> Result<Vec<PathBuf>, Error> {
+    let mut files = vec![];
+
+    for input in inputs {
+        if input.is_dir() {
+            let mut found = vec![];
+            for entry in fs::read_dir(input)? {
+                let path = entry?.path();
+                if path.is_file() && is_json(&path) {
+                    found.push(path);
+                }
+            }
+            found.sort();
+            files.extend(found);
+        } else {
+            files.push(input.clone());
+        }
+    }
+
+    Ok(files)
+}
+
+fn jobs(opt: &Opt) -> Result<Vec<Job>, Error> {
+    let inputs = expand_inputs(&opt.inputs)?;
+
+    if opt.outputs.is_empty() {
+        return Ok(inputs
+            .into_iter()
+            .map(|input| Job::new(input, None))
+            .collect());
+    }
+
+    if opt.outputs.len() != inputs.len() {
+        return Err(format_err!(
+            "{} outputs given for {} inputs",
+            opt.outputs.len(),
+            inputs.len()
+        ));
+    }
+
+    Ok(inputs
+        .into_iter()
+        .zip(opt.outputs.iter().cloned())
+        .map(|(input, output)| Job::new(input, Some(output)))
+        .collect())
 }
 
-#[derive(Debug, Deserialize)]
-struct Piskelxywh {
-    x: u32,
-    y: u32,
-    w: u32,
-    h: u32,
+fn write(job: &Job, opt: &Opt) -> Result<(), Error> {
+    for (path, contents) in job.generate(opt)? {
+        if path == Path::new("-") {
+            print!("{}", contents);
+        } else {
+            fs::write(&path, contents)?;
+            eprintln!("{} -> {}", job.input.display(), path.display());
+        }
+    }
+
+    Ok(())
 }
 
-#[derive(Debug, Deserialize)]
-struct Piskelwh {
-    w: u32,
-    h: u32,
+/// Returns the outputs that don't match what would be generated.
+fn check(job: &Job, opt: &Opt) -> Result<Vec<PathBuf>, Error> {
+    let mut stale = vec![];
+
+    for (path, contents) in job.generate(opt)? {
+        if fs::read_to_string(&path).ok().as_ref() != Some(&contents) {
+            stale.push(path);
+        }
+    }
+
+    Ok(stale)
 }
 
-#[derive(Debug, Serialize)]
-struct AmethystSheet {
-    spritesheet_width: f64,
-    spritesheet_height: f64,
-    sprites: Vec<AmethystSprite>,
+fn watch(opt: &Opt, jobs: &[Job]) -> Result<(), Error> {
+    let (tx, rx) = channel();
+    let mut watcher = notify::watcher(tx, Duration::from_millis(200))?;
+    for input in &opt.inputs {
+        watcher.watch(input, RecursiveMode::NonRecursive)?;
+    }
+    eprintln!("watching for changes, Ctrl-C to stop");
+
+    loop {
+        let changed = match rx.recv()? {
+            DebouncedEvent::Create(path)
+            | DebouncedEvent::Write(path)
+            | DebouncedEvent::Rename(_, path) => path,
+            DebouncedEvent::Error(e, _) => return Err(e.into()),
+            _ => continue,
+        };
+
+        // files added to a watched directory get the default output
+        let new_job;
+        let job = match jobs.iter().find(|job| same_file(&job.input, &changed)) {
+            Some(job) => job,
+            None if is_json(&changed) => {
+                new_job = Job::new(changed.clone(), None);
+                &new_job
+            }
+            None => continue,
+        };
+
+        // a half written export shouldn't stop the watch
+        if let Err(e) = write(job, opt) {
+            eprintln!("error: {}: {}", job.input.display(), e);
+        }
+    }
 }
 
-#[derive(Debug, Serialize)]
-struct AmethystSprite {
-    x: f64,
-    y: f64,
-    width: f64,
-    height: f64,
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
 }
 
 fn main() -> Result<(), Error> {
-    // Open the file in read-only mode.
-    let path = std::env::args().nth(1).unwrap();
-    let file = File::open(path)?;
-
-    // Read the JSON contents of the file as an instance of `User`.
-    let u: PiskelSheet = serde_json::from_reader(file)?;
-
-    let mut out = AmethystSheet {
-        spritesheet_width: f64::from(u.meta.size.w),
-        spritesheet_height: f64::from(u.meta.size.h),
-        sprites: vec![],
-    };
-
-    for sprite in u.frames.values() {
-        out.sprites.push(AmethystSprite {
-            x: f64::from(sprite.frame.x),
-            y: f64::from(sprite.frame.y),
-            width: f64::from(sprite.frame.w),
-            height: f64::from(sprite.frame.h),
-        });
-    }
-
-    println!(
-        "{}",
-        ron::ser::to_string_pretty(&out, ron::ser::PrettyConfig::default())?
-    );
+    let opt = Opt::from_args();
+
+    match &opt.command {
+        Some(Command::Pack(pack)) => return pack::pack(pack),
+        Some(Command::Tileset(tileset)) => return tileset::convert(tileset),
+        None => {}
+    }
+
+    if opt.check && opt.outputs.iter().any(|output| output == Path::new("-")) {
+        return Err(err_msg(
+            "--check compares against files, it can't check -o -",
+        ));
+    }
+
+    let jobs = jobs(&opt)?;
+
+    if jobs.is_empty() {
+        return Err(err_msg("no JSON files found"));
+    }
+
+    if opt.check {
+        let mut stale = vec![];
+        for job in &jobs {
+            stale.extend(check(job, &opt)?);
+        }
+
+        for path in &stale {
+            eprintln!("out of date: {}", path.display());
+        }
+
+        if !stale.is_empty() {
+            return Err(format_err!("{} files need regenerating", stale.len()));
+        }
+
+        return Ok(());
+    }
+
+    for job in &jobs {
+        write(job, &opt)?;
+    }
+
+    if opt.watch {
+        watch(&opt, &jobs)?;
+    }
 
     Ok(())
 }
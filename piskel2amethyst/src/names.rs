@@ -0,0 +1,72 @@
+//! Frame names like `walk_down_3.png`, which say which clip a frame belongs
+//! to and where in it.
+
+use amethyst::{AmethystAnimations, AmethystClip, ClipMode, ClipName};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Splits `walk_down_3.png` into `("walk_down", Some(3))`.
+pub fn split_frame_name(name: &str) -> (&str, Option<u32>) {
+    let stem = Path::new(name)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(name);
+    let digits = stem.len() - stem.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    let (prefix, index) = stem.split_at(stem.len() - digits);
+    let prefix = prefix.trim_end_matches(&['_', '-', ' '][..]);
+
+    (prefix, index.parse().ok())
+}
+
+/// Sorts by prefix, then by index as a number so `walk_10` comes after
+/// `walk_9`.
+pub fn sort_key(name: &str) -> (&str, bool, Option<u32>, &str) {
+    let (prefix, index) = split_frame_name(name);
+    (prefix, index.is_none(), index, name)
+}
+
+/// Clips from frame names and durations, given in sprite order. Frames whose
+/// name isn't a clip go into the idle clip.
+pub fn clips_from_names<'a, I>(frames: I) -> AmethystAnimations
+where
+    I: IntoIterator<Item = (&'a str, f32)>,
+{
+    let mut clips = BTreeMap::new();
+
+    for (index, (name, duration)) in frames.into_iter().enumerate() {
+        let clip = ClipName::parse(split_frame_name(name).0).unwrap_or(ClipName::Idle);
+        clips
+            .entry(clip)
+            .or_insert_with(|| AmethystClip {
+                frames: vec![],
+                mode: ClipMode::Loop,
+            })
+            .frames
+            .push((index, duration));
+    }
+
+    AmethystAnimations { clips }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_the_index_off_the_name() {
+        assert_eq!(split_frame_name("walk_down_3.png"), ("walk_down", Some(3)));
+        assert_eq!(split_frame_name("FRONT10.png"), ("FRONT", Some(10)));
+        assert_eq!(split_frame_name("idle.png"), ("idle", None));
+    }
+
+    #[test]
+    fn sorts_indices_as_numbers_and_unnumbered_last() {
+        let mut names = vec!["FRONT10.png", "FRONT.png", "FRONT9.png", "BACK1.png"];
+        names.sort_by_key(|name| sort_key(name));
+
+        assert_eq!(
+            names,
+            vec!["BACK1.png", "FRONT9.png", "FRONT10.png", "FRONT.png"]
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! Packs a directory of loose frame PNGs into one atlas, so sprites can be
+//! kept as separate files instead of going through an editor's export.
+
+use amethyst::{self, AmethystSheet, AmethystSprite, DEFAULT_FRAME_DURATION};
+use failure::Error;
+use image::{self, RgbaImage};
+use names;
+use std::cmp::Reverse;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, StructOpt)]
+pub struct PackOpt {
+    /// Directory of frame PNGs, named like `walk_down_0.png`
+    #[structopt(parse(from_os_str))]
+    frames: PathBuf,
+
+    /// Where to write the sheet RON, the atlas goes next to it as PNG.
+    /// Defaults to <frames>.ron next to the directory.
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    output: Option<PathBuf>,
+
+    /// Transparent pixels between frames
+    #[structopt(short = "p", long = "padding", default_value = "1")]
+    padding: u32,
+
+    /// Pixels to repeat each frame's edges by, so filtering near the border
+    /// doesn't pick up the neighbouring frame
+    #[structopt(short = "e", long = "extrude", default_value = "1")]
+    extrude: u32,
+
+    /// Widest the atlas may get. Defaults to the smallest power of two that
+    /// keeps it roughly square.
+    #[structopt(long = "max-width")]
+    max_width: Option<u32>,
+
+    /// Also write animation clips named after the frames to <output>.anim.ron
+    #[structopt(short = "a", long = "animations")]
+    animations: bool,
+}
+
+/// A frame and where it lands in the atlas.
+struct Frame {
+    name: String,
+    image: RgbaImage,
+    x: u32,
+    y: u32,
+}
+
+fn read_frames(dir: &Path) -> Result<Vec<Frame>, Error> {
+    let mut frames = vec![];
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_file() || path.extension() != Some(OsStr::new("png")) {
+            continue;
+        }
+
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| format_err!("{} isn't a UTF-8 name", path.display()))?
+            .to_string();
+        let image = image::open(&path)
+            .map_err(|e| format_err!("{}: {}", path.display(), e))?
+            .to_rgba();
+
+        frames.push(Frame {
+            name,
+            image,
+            x: 0,
+            y: 0,
+        });
+    }
+
+    frames.sort_by(|a, b| names::sort_key(&a.name).cmp(&names::sort_key(&b.name)));
+    Ok(frames)
+}
+
+/// Places frames in rows, tallest first, and returns the atlas size. Each
+/// frame takes up its size plus the extrusion on every side and the padding
+/// after it.
+fn shelf_pack(frames: &mut [Frame], opt: &PackOpt) -> Result<(u32, u32), Error> {
+    let cell = |frame: &Frame| {
+        let (w, h) = frame.image.dimensions();
+        let border = opt.extrude * 2 + opt.padding;
+        (w + border, h + border)
+    };
+
+    let widest = frames.iter().map(|frame| cell(frame).0).max().unwrap_or(0);
+    let width = match opt.max_width {
+        Some(max) if max < widest => {
+            return Err(format_err!(
+                "a frame needs {} pixels but --max-width is {}",
+                widest,
+                max
+            ))
+        }
+        Some(max) => max,
+        None => {
+            // a few big frames can add up to more than fits in a u32
+            let area: u64 = frames
+                .iter()
+                .map(|frame| {
+                    let (w, h) = cell(frame);
+                    u64::from(w) * u64::from(h)
+                })
+                .sum();
+            widest
+                .max(((area as f64).sqrt().ceil()) as u32)
+                .next_power_of_two()
+        }
+    };
+
+    let mut order: Vec<usize> = (0..frames.len()).collect();
+    order.sort_by_key(|&index| Reverse(cell(&frames[index]).1));
+
+    let (mut x, mut y, mut row_height) = (0, 0, 0);
+    for index in order {
+        let (w, h) = cell(&frames[index]);
+        if x + w > width {
+            x = 0;
+            y += row_height;
+            row_height = 0;
+        }
+
+        frames[index].x = x + opt.extrude;
+        frames[index].y = y + opt.extrude;
+        x += w;
+        row_height = row_height.max(h);
+    }
+
+    Ok((width, y + row_height))
+}
+
+/// Copies each frame into place, repeating its edge pixels `extrude` times
+/// outwards.
+fn draw(frames: &[Frame], width: u32, height: u32, extrude: u32) -> RgbaImage {
+    let mut atlas = RgbaImage::new(width, height);
+
+    for frame in frames {
+        let (w, h) = frame.image.dimensions();
+        if w == 0 || h == 0 {
+            continue;
+        }
+
+        for dy in 0..h + extrude * 2 {
+            for dx in 0..w + extrude * 2 {
+                let sx = dx.saturating_sub(extrude).min(w - 1);
+                let sy = dy.saturating_sub(extrude).min(h - 1);
+                let pixel = *frame.image.get_pixel(sx, sy);
+                atlas.put_pixel(frame.x - extrude + dx, frame.y - extrude + dy, pixel);
+            }
+        }
+    }
+
+    atlas
+}
+
+pub fn pack(opt: &PackOpt) -> Result<(), Error> {
+    let mut frames = read_frames(&opt.frames)?;
+    if frames.is_empty() {
+        return Err(format_err!("no PNG files in {}", opt.frames.display()));
+    }
+
+    let (width, height) = shelf_pack(&mut frames, opt)?;
+    let atlas = draw(&frames, width, height, opt.extrude);
+
+    let sheet = AmethystSheet {
+        spritesheet_width: f64::from(width),
+        spritesheet_height: f64::from(height),
+        sprites: frames
+            .iter()
+            .map(|frame| AmethystSprite {
+                x: f64::from(frame.x),
+                y: f64::from(frame.y),
+                width: f64::from(frame.image.width()),
+                height: f64::from(frame.image.height()),
+                offsets: None,
+            })
+            .collect(),
+    };
+
+    let output = opt
+        .output
+        .clone()
+        .unwrap_or_else(|| opt.frames.with_extension("ron"));
+    let png = output.with_extension("png");
+
+    atlas.save(&png)?;
+    eprintln!("{} -> {}", opt.frames.display(), png.display());
+    fs::write(&output, amethyst::to_ron(&sheet)?)?;
+    eprintln!("{} -> {}", opt.frames.display(), output.display());
+
+    if opt.animations {
+        let clips = names::clips_from_names(
+            frames
+                .iter()
+                .map(|frame| (frame.name.as_str(), DEFAULT_FRAME_DURATION)),
+        );
+        let path = output.with_extension("anim.ron");
+        fs::write(&path, amethyst::to_ron(&clips)?)?;
+        eprintln!("{} -> {}", opt.frames.display(), path.display());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn opt(padding: u32, extrude: u32) -> PackOpt {
+        PackOpt {
+            frames: PathBuf::new(),
+            output: None,
+            padding,
+            extrude,
+            max_width: None,
+            animations: false,
+        }
+    }
+
+    /// A frame whose pixels' red channel counts up from 1, row by row.
+    fn frame(name: &str, width: u32, height: u32) -> Frame {
+        Frame {
+            name: name.into(),
+            image: RgbaImage::from_fn(width, height, |x, y| {
+                Rgba([(1 + x + y * width) as u8, 0, 0, 255])
+            }),
+            x: 0,
+            y: 0,
+        }
+    }
+
+    #[test]
+    fn packs_rows_tallest_first() {
+        let mut frames = vec![frame("a", 4, 4), frame("b", 2, 2), frame("c", 4, 2)];
+
+        // cells are 7x7, 5x5 and 7x5, so 109 pixels round up to 16 wide
+        let size = shelf_pack(&mut frames, &opt(1, 1)).unwrap();
+        let positions: Vec<_> = frames.iter().map(|frame| (frame.x, frame.y)).collect();
+
+        assert_eq!(size, (16, 12));
+        assert_eq!(positions, vec![(1, 1), (8, 1), (1, 8)]);
+    }
+
+    #[test]
+    fn rejects_frames_wider_than_max_width() {
+        let mut frames = vec![frame("a", 8, 8)];
+        let opt = PackOpt {
+            max_width: Some(8),
+            ..opt(1, 1)
+        };
+
+        assert!(shelf_pack(&mut frames, &opt).is_err());
+    }
+
+    #[test]
+    fn extrudes_edges_and_leaves_padding_clear() {
+        let mut frames = vec![frame("a", 2, 2)];
+        let (width, height) = shelf_pack(&mut frames, &opt(1, 1)).unwrap();
+        let atlas = draw(&frames, width, height, 1);
+
+        let red: Vec<Vec<u8>> = (0..5)
+            .map(|y| (0..5).map(|x| atlas.get_pixel(x, y)[0]).collect())
+            .collect();
+
+        assert_eq!((width, height), (8, 5));
+        assert_eq!(
+            red,
+            vec![
+                vec![1, 1, 2, 2, 0],
+                vec![1, 1, 2, 2, 0],
+                vec![3, 3, 4, 4, 0],
+                vec![3, 3, 4, 4, 0],
+                vec![0, 0, 0, 0, 0],
+            ]
+        );
+        assert_eq!(atlas.get_pixel(4, 0)[3], 0);
+    }
+}
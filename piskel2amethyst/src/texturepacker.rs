@@ -0,0 +1,431 @@
+//! The JSON sheet layout TexturePacker introduced, which Piskel and Aseprite
+//! export too. Frames come either as an object keyed by name ("hash") or as
+//! an array of frames carrying their own `filename` ("array").
+
+use amethyst::{
+    AmethystAnimations, AmethystClip, AmethystSheet, AmethystSprite, ClipMode, ClipName,
+    DEFAULT_FRAME_DURATION,
+};
+use failure::Error;
+use names;
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Read;
+use std::str::FromStr;
+
+/// Which editor wrote the JSON. They share a layout but not frame order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Frames named `<name><index>`, in no particular order.
+    Piskel,
+    /// Frames in the order they're drawn, which frame tags index into.
+    Aseprite,
+    /// Anything else with this layout. Ordered by name like Piskel.
+    TexturePacker,
+}
+
+impl Format {
+    pub const NAMES: &'static [&'static str] = &["piskel", "aseprite", "texturepacker"];
+
+    /// Guesses from the `meta.app` the editor writes.
+    fn detect(app: &str) -> Format {
+        let app = app.to_lowercase();
+        if app.contains("piskel") {
+            Format::Piskel
+        } else if app.contains("aseprite") {
+            Format::Aseprite
+        } else {
+            Format::TexturePacker
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_lowercase().as_str() {
+            "piskel" => Ok(Format::Piskel),
+            "aseprite" => Ok(Format::Aseprite),
+            "texturepacker" => Ok(Format::TexturePacker),
+            _ => Err(format!(
+                "unknown format {:?}, expected one of {}",
+                name,
+                Format::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JsonSheet {
+    frames: Frames,
+    meta: Meta,
+}
+
+/// Frames by name, in the order the file lists them.
+#[derive(Debug)]
+struct Frames(Vec<(String, Image)>);
+
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+struct Image {
+    frame: Xywh,
+    #[serde(default)]
+    rotated: bool,
+    #[serde(default)]
+    trimmed: bool,
+    spriteSourceSize: Xywh,
+    sourceSize: Wh,
+    /// Milliseconds, only present in exports from editors that have timing.
+    #[serde(default)]
+    duration: Option<u32>,
+}
+
+/// An entry of the array layout.
+#[derive(Debug, Deserialize)]
+struct NamedImage {
+    filename: String,
+    #[serde(flatten)]
+    image: Image,
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+struct Meta {
+    #[serde(default)]
+    app: String,
+    size: Wh,
+    #[serde(default)]
+    frameTags: Vec<Tag>,
+}
+
+/// A named range of frames, `from` and `to` inclusive.
+#[derive(Debug, Deserialize)]
+struct Tag {
+    name: String,
+    from: usize,
+    to: usize,
+    /// `forward`, `reverse`, `pingpong` or `pingpong_reverse`.
+    #[serde(default)]
+    direction: Option<String>,
+    /// How many times Aseprite plays the tag, missing or `0` for forever.
+    #[serde(default)]
+    repeat: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Xywh {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct Wh {
+    w: u32,
+    h: u32,
+}
+
+impl<'de> Deserialize<'de> for Frames {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FramesVisitor;
+
+        impl<'de> Visitor<'de> for FramesVisitor {
+            type Value = Frames;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map or an array of frames")
+            }
+
+            // a HashMap would lose the order Aseprite's frame tags rely on
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Frames, A::Error> {
+                let mut frames = vec![];
+                while let Some(entry) = map.next_entry()? {
+                    frames.push(entry);
+                }
+                Ok(Frames(frames))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Frames, A::Error> {
+                let mut frames = vec![];
+                while let Some(NamedImage { filename, image }) = seq.next_element()? {
+                    frames.push((filename, image));
+                }
+                Ok(Frames(frames))
+            }
+        }
+
+        deserializer.deserialize_any(FramesVisitor)
+    }
+}
+
+/// Frames in the order they were drawn. Aseprite lists them that way, the
+/// others are sorted by the index at the end of their names.
+fn ordered_frames(frames: &Frames, format: Format) -> Vec<(&str, &Image)> {
+    let mut ordered: Vec<_> = frames
+        .0
+        .iter()
+        .map(|(name, image)| (name.as_str(), image))
+        .collect();
+
+    if format != Format::Aseprite {
+        ordered.sort_by_key(|(name, _)| names::sort_key(name));
+    }
+
+    ordered
+}
+
+fn sprite(image: &Image) -> AmethystSprite {
+    let frame = &image.frame;
+    let source = &image.spriteSourceSize;
+    let full = &image.sourceSize;
+
+    let offsets = if image.trimmed {
+        // distance from the trimmed frame's center to the untrimmed one's
+        Some([
+            f64::from(full.w) / 2.0 - (f64::from(source.x) + f64::from(source.w) / 2.0),
+            f64::from(source.y) + f64::from(source.h) / 2.0 - f64::from(full.h) / 2.0,
+        ])
+    } else {
+        None
+    };
+
+    if image.rotated {
+        eprintln!("warning: rotated frames aren't supported, exporting as is");
+    }
+
+    AmethystSprite {
+        x: f64::from(frame.x),
+        y: f64::from(frame.y),
+        width: f64::from(frame.w),
+        height: f64::from(frame.h),
+        offsets,
+    }
+}
+
+/// The clip a frame tag plays, following its direction and repeat count.
+fn tag_clip<F>(tag: &Tag, timed: F) -> AmethystClip
+where
+    F: Fn(usize) -> (usize, f32),
+{
+    let mut frames: Vec<_> = (tag.from..=tag.to).map(timed).collect();
+
+    let direction = tag.direction.as_ref().map_or("forward", |d| d.as_str());
+    if direction == "reverse" || direction == "pingpong_reverse" {
+        frames.reverse();
+    }
+
+    let mode = match (direction, tag.repeat.as_deref()) {
+        ("pingpong", _) | ("pingpong_reverse", _) => ClipMode::PingPong,
+        (_, Some("1")) => ClipMode::Once,
+        ("forward", _) | ("reverse", _) => ClipMode::Loop,
+        (other, _) => {
+            eprintln!(
+                "warning: unknown direction {:?} on tag {:?}",
+                other, tag.name
+            );
+            ClipMode::Loop
+        }
+    };
+
+    AmethystClip { frames, mode }
+}
+
+/// Clips from the sheet's frame tags if it has any, otherwise from frame
+/// names like `walk_down_0.png`. A sheet whose frames don't name a clip
+/// becomes a single idle clip.
+fn animations(sheet: &JsonSheet, frames: &[(&str, &Image)]) -> AmethystAnimations {
+    let timed = |index: usize| {
+        let duration = frames[index]
+            .1
+            .duration
+            .map_or(DEFAULT_FRAME_DURATION, |ms| ms as f32 / 1000.0);
+        (index, duration)
+    };
+
+    if sheet.meta.frameTags.is_empty() {
+        return names::clips_from_names(
+            frames
+                .iter()
+                .enumerate()
+                .map(|(index, (name, _))| (*name, timed(index).1)),
+        );
+    }
+
+    let mut clips = BTreeMap::new();
+    for tag in &sheet.meta.frameTags {
+        match ClipName::parse(&tag.name) {
+            Some(clip) if tag.from <= tag.to && tag.to < frames.len() => {
+                clips.insert(clip, tag_clip(tag, timed));
+            }
+            _ => eprintln!("warning: skipping frame tag {:?}", tag.name),
+        }
+    }
+
+    AmethystAnimations { clips }
+}
+
+impl JsonSheet {
+    pub fn read<R: Read>(reader: R) -> Result<Self, Error> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// The editor that wrote this sheet, going by `meta.app`.
+    pub fn detect_format(&self) -> Format {
+        Format::detect(&self.meta.app)
+    }
+
+    /// The sprite sheet, and the animation clips cut from it.
+    pub fn convert(&self, format: Format) -> (AmethystSheet, AmethystAnimations) {
+        let frames = ordered_frames(&self.frames, format);
+
+        let sheet = AmethystSheet {
+            spritesheet_width: f64::from(self.meta.size.w),
+            spritesheet_height: f64::from(self.meta.size.h),
+            sprites: frames.iter().map(|(_, image)| sprite(image)).collect(),
+        };
+
+        (sheet, animations(self, &frames))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The fields of an untrimmed 16x16 frame `x` pixels along the atlas,
+    /// shown for 200ms.
+    fn image(x: u32) -> String {
+        format!(
+            r#""frame": {{ "x": {}, "y": 0, "w": 16, "h": 16 }},
+                "spriteSourceSize": {{ "x": 0, "y": 0, "w": 16, "h": 16 }},
+                "sourceSize": {{ "w": 16, "h": 16 }},
+                "duration": 200"#,
+            x
+        )
+    }
+
+    fn tag(from: usize, to: usize, direction: &str, repeat: Option<&str>) -> Tag {
+        Tag {
+            name: "walk_down".into(),
+            from,
+            to,
+            direction: Some(direction.into()),
+            repeat: repeat.map(String::from),
+        }
+    }
+
+    fn xs(sheet: &AmethystSheet) -> Vec<f64> {
+        sheet.sprites.iter().map(|sprite| sprite.x).collect()
+    }
+
+    #[test]
+    fn offsets_trimmed_frames_back_to_the_untrimmed_center() {
+        let xywh = |x, y, w, h| Xywh { x, y, w, h };
+        let mut image = Image {
+            frame: xywh(0, 0, 16, 16),
+            rotated: false,
+            trimmed: true,
+            // the top left quarter of a 32x32 frame, y counting down
+            spriteSourceSize: xywh(0, 0, 16, 16),
+            sourceSize: Wh { w: 32, h: 32 },
+            duration: None,
+        };
+
+        // drawn 8 left and 8 up of where a centered 16x16 sprite would go
+        assert_eq!(sprite(&image).offsets, Some([8.0, -8.0]));
+
+        image.trimmed = false;
+        assert_eq!(sprite(&image).offsets, None);
+    }
+
+    #[test]
+    fn detects_the_editor_from_meta_app() {
+        assert_eq!(Format::detect("Piskel"), Format::Piskel);
+        assert_eq!(Format::detect("http://www.aseprite.org/"), Format::Aseprite);
+        assert_eq!(
+            Format::detect("https://www.codeandweb.com/texturepacker"),
+            Format::TexturePacker
+        );
+        assert_eq!(Format::detect(""), Format::TexturePacker);
+    }
+
+    #[test]
+    fn reads_an_aseprite_array_export() {
+        let json = format!(
+            r#"{{
+                "frames": [
+                    {{ "filename": "player 0.aseprite", {} }},
+                    {{ "filename": "player 1.aseprite", {} }}
+                ],
+                "meta": {{
+                    "app": "http://www.aseprite.org/",
+                    "size": {{ "w": 32, "h": 16 }},
+                    "frameTags": [{{ "name": "walk_down", "from": 0, "to": 1, "direction": "forward" }}]
+                }}
+            }}"#,
+            image(0),
+            image(16)
+        );
+        let sheet = JsonSheet::read(json.as_bytes()).unwrap();
+
+        assert_eq!(sheet.detect_format(), Format::Aseprite);
+        let (amethyst, animations) = sheet.convert(sheet.detect_format());
+        assert_eq!(xs(&amethyst), vec![0.0, 16.0]);
+
+        let walk = &animations.clips[&ClipName::WalkDown];
+        assert_eq!(walk.frames, vec![(0, 0.2), (1, 0.2)]);
+        assert_eq!(walk.mode, ClipMode::Loop);
+    }
+
+    #[test]
+    fn aseprite_hash_tags_index_into_file_order() {
+        // listed 10 before 9, which sorting by name would swap
+        let json = format!(
+            r#"{{
+                "frames": {{
+                    "player 10.aseprite": {{ {} }},
+                    "player 9.aseprite": {{ {} }}
+                }},
+                "meta": {{
+                    "app": "http://www.aseprite.org/",
+                    "size": {{ "w": 32, "h": 16 }},
+                    "frameTags": [{{ "name": "idle", "from": 0, "to": 0 }}]
+                }}
+            }}"#,
+            image(0),
+            image(16)
+        );
+        let sheet = JsonSheet::read(json.as_bytes()).unwrap();
+
+        let (aseprite, animations) = sheet.convert(Format::Aseprite);
+        assert_eq!(xs(&aseprite), vec![0.0, 16.0]);
+        assert_eq!(animations.clips[&ClipName::Idle].frames, vec![(0, 0.2)]);
+
+        let (sorted, _) = sheet.convert(Format::TexturePacker);
+        assert_eq!(xs(&sorted), vec![16.0, 0.0]);
+    }
+
+    #[test]
+    fn pingpong_reverse_plays_backwards_then_forwards() {
+        let clip = tag_clip(&tag(1, 3, "pingpong_reverse", None), |i| (i, 0.1));
+
+        assert_eq!(clip.frames, vec![(3, 0.1), (2, 0.1), (1, 0.1)]);
+        assert_eq!(clip.mode, ClipMode::PingPong);
+    }
+
+    #[test]
+    fn playing_a_tag_once_is_a_once_clip() {
+        let forward = tag_clip(&tag(0, 1, "forward", Some("1")), |i| (i, 0.1));
+        let reverse = tag_clip(&tag(0, 1, "reverse", Some("1")), |i| (i, 0.1));
+        let forever = tag_clip(&tag(0, 1, "forward", Some("0")), |i| (i, 0.1));
+
+        assert_eq!(forward.mode, ClipMode::Once);
+        assert_eq!(reverse.mode, ClipMode::Once);
+        assert_eq!(reverse.frames, vec![(1, 0.1), (0, 0.1)]);
+        assert_eq!(forever.mode, ClipMode::Loop);
+    }
+}
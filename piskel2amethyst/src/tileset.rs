@@ -0,0 +1,61 @@
+//! Writes the sprite sheet game-core cuts from a Tiled tileset as a
+//! standalone RON file, for loading it like any other sheet.
+
+use amethyst::{self, AmethystSheet, AmethystSprite};
+use failure::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tilesheet::Tileset;
+
+#[derive(Debug, StructOpt)]
+pub struct TilesetOpt {
+    /// Tiled tileset to convert
+    #[structopt(parse(from_os_str))]
+    tsx: PathBuf,
+
+    /// Where to write the RON, `-` prints to stdout. Defaults to <tsx>.ron
+    /// next to the tileset.
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    output: Option<PathBuf>,
+}
+
+pub fn convert(opt: &TilesetOpt) -> Result<(), Error> {
+    let tileset =
+        Tileset::open(&opt.tsx).map_err(|e| format_err!("{}: {}", opt.tsx.display(), e))?;
+
+    let sheet = AmethystSheet {
+        spritesheet_width: f64::from(tileset.image_width),
+        spritesheet_height: f64::from(tileset.image_height),
+        sprites: tileset
+            .tiles()
+            .into_iter()
+            .map(|tile| AmethystSprite {
+                x: f64::from(tile.x),
+                y: f64::from(tile.y),
+                width: f64::from(tile.width),
+                height: f64::from(tile.height),
+                offsets: None,
+            })
+            .collect(),
+    };
+    let ron = amethyst::to_ron(&sheet)?;
+
+    let output = opt
+        .output
+        .clone()
+        .unwrap_or_else(|| opt.tsx.with_extension("ron"));
+    if output == Path::new("-") {
+        print!("{}", ron);
+    } else {
+        fs::write(&output, ron)?;
+        eprintln!(
+            "{} -> {} ({} tiles of {})",
+            opt.tsx.display(),
+            output.display(),
+            sheet.sprites.len(),
+            tileset.image
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,360 @@
+//! Cuts the image of a Tiled tileset (`.tsx`) into one rectangle per tile,
+//! ready to become an amethyst `SpriteSheet`.
+//!
+//! Every tile of the image gets a rectangle, not only the ones the tsx has
+//! `<tile>` metadata for, so sprite numbers line up with tile ids.
+
+extern crate xml;
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::{error, fmt};
+use xml::reader::{EventReader, XmlEvent};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Xml(xml::reader::Error),
+    /// The file doesn't start with a `<tileset>` element.
+    NotATileset,
+    /// The tileset is a collection of separate images, which can't be one
+    /// sprite sheet.
+    NoImage,
+    MissingAttribute(&'static str),
+    BadAttribute {
+        name: &'static str,
+        value: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Xml(e) => write!(f, "{}", e),
+            Error::NotATileset => write!(f, "not a tileset"),
+            Error::NoImage => write!(f, "tileset has no image"),
+            Error::MissingAttribute(name) => write!(f, "missing attribute {:?}", name),
+            Error::BadAttribute { name, value } => {
+                write!(f, "attribute {:?} can't be {:?}", name, value)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<xml::reader::Error> for Error {
+    fn from(e: xml::reader::Error) -> Self {
+        Error::Xml(e)
+    }
+}
+
+/// Where a tile is in the image, in pixels from the top left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tileset {
+    pub name: String,
+    /// Path of the image, relative to the tsx.
+    pub image: String,
+    pub image_width: u32,
+    pub image_height: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    /// Pixels around the edge of the image before the first tile.
+    pub margin: u32,
+    /// Pixels between neighbouring tiles.
+    pub spacing: u32,
+    /// As written in the tsx. Older files leave these out, then they're
+    /// worked out from the image size.
+    pub columns: Option<u32>,
+    pub tile_count: Option<u32>,
+}
+
+impl Tileset {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Tileset::read(File::open(path)?)
+    }
+
+    /// Reads a tsx file. Only the `<tileset>` and its `<image>` matter here,
+    /// everything else is skipped.
+    pub fn read<R: Read>(reader: R) -> Result<Self, Error> {
+        let mut tileset = None;
+        let mut depth = 0;
+
+        for event in EventReader::new(reader) {
+            match event? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => {
+                    depth += 1;
+                    let attribute = |wanted: &str| {
+                        attributes
+                            .iter()
+                            .find(|a| a.name.local_name == wanted)
+                            .map(|a| a.value.as_str())
+                    };
+
+                    match (depth, name.local_name.as_str()) {
+                        (1, "tileset") => {
+                            tileset = Some(Tileset {
+                                name: attribute("name").unwrap_or_default().to_string(),
+                                image: String::new(),
+                                image_width: 0,
+                                image_height: 0,
+                                tile_width: required(attribute("tilewidth"), "tilewidth")?,
+                                tile_height: required(attribute("tileheight"), "tileheight")?,
+                                margin: optional(attribute("margin"), "margin")?.unwrap_or(0),
+                                spacing: optional(attribute("spacing"), "spacing")?.unwrap_or(0),
+                                columns: optional(attribute("columns"), "columns")?,
+                                tile_count: optional(attribute("tilecount"), "tilecount")?,
+                            });
+                        }
+                        (1, _) => return Err(Error::NotATileset),
+                        // images inside <tile> belong to collection tilesets
+                        (2, "image") => {
+                            let tileset = tileset.as_mut().ok_or(Error::NotATileset)?;
+                            tileset.image = attribute("source")
+                                .ok_or(Error::MissingAttribute("source"))?
+                                .to_string();
+                            tileset.image_width = required(attribute("width"), "width")?;
+                            tileset.image_height = required(attribute("height"), "height")?;
+                        }
+                        _ => {}
+                    }
+                }
+                XmlEvent::EndElement { .. } => depth -= 1,
+                _ => {}
+            }
+        }
+
+        match tileset {
+            Some(ref tileset) if tileset.image.is_empty() => Err(Error::NoImage),
+            Some(tileset) => Ok(tileset),
+            None => Err(Error::NotATileset),
+        }
+    }
+
+    /// Tiles per row, from the tsx or however many fit across the image.
+    pub fn columns(&self) -> u32 {
+        self.columns
+            .unwrap_or_else(|| fit(self.image_width, self.tile_width, self.margin, self.spacing))
+    }
+
+    /// Every tile in the image, from the tsx or however many fit.
+    pub fn tile_count(&self) -> u32 {
+        self.tile_count.unwrap_or_else(|| {
+            let rows = fit(
+                self.image_height,
+                self.tile_height,
+                self.margin,
+                self.spacing,
+            );
+            self.columns() * rows
+        })
+    }
+
+    /// The rectangle of tile `id`, counted from 0 like in the tsx.
+    pub fn tile(&self, id: u32) -> Option<Rect> {
+        let columns = self.columns();
+        if id >= self.tile_count() || columns == 0 {
+            return None;
+        }
+
+        let (column, row) = (id % columns, id / columns);
+        Some(Rect {
+            x: self.margin + column * (self.tile_width + self.spacing),
+            y: self.margin + row * (self.tile_height + self.spacing),
+            width: self.tile_width,
+            height: self.tile_height,
+        })
+    }
+
+    /// One rectangle per tile, indexed by tile id.
+    pub fn tiles(&self) -> Vec<Rect> {
+        (0..self.tile_count())
+            .filter_map(|id| self.tile(id))
+            .collect()
+    }
+}
+
+/// How many tiles of `size` fit in `length` pixels with a margin on both
+/// ends and spacing between them.
+fn fit(length: u32, size: u32, margin: u32, spacing: u32) -> u32 {
+    if size == 0 {
+        return 0;
+    }
+
+    let usable = length.saturating_sub(2 * margin);
+    if usable < size {
+        return 0;
+    }
+
+    // the last tile has no spacing after it
+    (usable - size) / (size + spacing) + 1
+}
+
+fn optional(value: Option<&str>, name: &'static str) -> Result<Option<u32>, Error> {
+    match value {
+        Some(value) => value.parse().map(Some).map_err(|_| Error::BadAttribute {
+            name,
+            value: value.to_string(),
+        }),
+        None => Ok(None),
+    }
+}
+
+fn required(value: Option<&str>, name: &'static str) -> Result<u32, Error> {
+    optional(value, name)?.ok_or(Error::MissingAttribute(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the header of resources/desert.tsx, with only two of its tiles
+    const DESERT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.2" tiledversion="1.2.0" name="desert" tilewidth="32" tileheight="32" spacing="1" margin="1" tilecount="48" columns="8">
+ <image source="tmw_desert_spacing.png" width="265" height="199"/>
+ <tile id="0" terrain="3,3,3,0"/>
+ <tile id="30">
+  <properties>
+   <property name="solid" type="bool" value="true"/>
+  </properties>
+ </tile>
+</tileset>
+"#;
+
+    fn desert() -> Tileset {
+        Tileset::read(DESERT.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn reads_the_tsx_header() {
+        let tileset = desert();
+
+        assert_eq!(tileset.name, "desert");
+        assert_eq!(tileset.image, "tmw_desert_spacing.png");
+        assert_eq!((tileset.image_width, tileset.image_height), (265, 199));
+        assert_eq!((tileset.tile_width, tileset.tile_height), (32, 32));
+        assert_eq!((tileset.margin, tileset.spacing), (1, 1));
+        assert_eq!(tileset.columns, Some(8));
+        assert_eq!(tileset.tile_count, Some(48));
+    }
+
+    #[test]
+    fn every_tile_gets_a_rect_not_just_the_ones_with_metadata() {
+        let tiles = desert().tiles();
+
+        assert_eq!(tiles.len(), 48);
+        assert_eq!(tiles[5], desert().tile(5).unwrap());
+    }
+
+    #[test]
+    fn margin_and_spacing() {
+        let tileset = desert();
+        let at = |id| {
+            let rect = tileset.tile(id).unwrap();
+            (rect.x, rect.y)
+        };
+
+        assert_eq!(at(0), (1, 1));
+        assert_eq!(at(1), (34, 1));
+        assert_eq!(at(7), (232, 1));
+        assert_eq!(at(8), (1, 34));
+        assert_eq!(at(47), (232, 166));
+        assert_eq!(tileset.tile(48), None);
+
+        // the last tile ends inside the image, before the margin
+        let last = tileset.tile(47).unwrap();
+        assert_eq!(last.x + last.width + tileset.margin, 265);
+        assert_eq!(last.y + last.height + tileset.margin, 199);
+    }
+
+    #[test]
+    fn works_out_columns_and_count_when_the_tsx_leaves_them_out() {
+        let mut tileset = desert();
+        tileset.columns = None;
+        tileset.tile_count = None;
+
+        assert_eq!(tileset.columns(), 8);
+        assert_eq!(tileset.tile_count(), 48);
+        assert_eq!(tileset.tiles(), desert().tiles());
+    }
+
+    #[test]
+    fn partial_tiles_at_the_edge_are_left_out() {
+        let tileset = Tileset {
+            name: "plain".into(),
+            image: "plain.png".into(),
+            image_width: 50,
+            image_height: 40,
+            tile_width: 16,
+            tile_height: 16,
+            margin: 0,
+            spacing: 0,
+            columns: None,
+            tile_count: None,
+        };
+
+        assert_eq!(tileset.columns(), 3);
+        assert_eq!(tileset.tile_count(), 6);
+        assert_eq!(
+            tileset.tile(4),
+            Some(Rect {
+                x: 16,
+                y: 16,
+                width: 16,
+                height: 16
+            })
+        );
+    }
+
+    #[test]
+    fn tiles_bigger_than_the_image() {
+        let mut tileset = desert();
+        tileset.columns = None;
+        tileset.tile_count = None;
+        tileset.tile_width = 300;
+
+        assert_eq!(tileset.tile_count(), 0);
+        assert!(tileset.tiles().is_empty());
+    }
+
+    #[test]
+    fn errors() {
+        let collection = r#"<tileset name="things" tilewidth="16" tileheight="16" tilecount="1" columns="0">
+ <tile id="0"><image source="thing.png" width="16" height="16"/></tile>
+</tileset>"#;
+        match Tileset::read(collection.as_bytes()) {
+            Err(Error::NoImage) => {}
+            other => panic!("expected NoImage, got {:?}", other),
+        }
+
+        match Tileset::read(r#"<map width="1" height="1"/>"#.as_bytes()) {
+            Err(Error::NotATileset) => {}
+            other => panic!("expected NotATileset, got {:?}", other),
+        }
+
+        match Tileset::read(r#"<tileset tilewidth="x" tileheight="16"/>"#.as_bytes()) {
+            Err(Error::BadAttribute {
+                name: "tilewidth", ..
+            }) => {}
+            other => panic!("expected BadAttribute, got {:?}", other),
+        }
+    }
+}